@@ -1,12 +1,30 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20260218_040456_create_tables;
+mod m20261019_000000_rename_note_title;
+mod m20261019_000001_unique_names;
+mod m20261019_000002_note_timestamps;
+mod m20261019_000003_saved_queries;
+mod m20261019_000004_smart_notebooks;
+mod m20261019_000005_note_attachments;
+mod m20261019_000006_note_status;
+
+pub use m20261019_000001_unique_names::MERGE_DUPLICATES;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20260218_040456_create_tables::Migration)]
+        vec![
+            Box::new(m20260218_040456_create_tables::Migration),
+            Box::new(m20261019_000000_rename_note_title::Migration),
+            Box::new(m20261019_000001_unique_names::Migration),
+            Box::new(m20261019_000002_note_timestamps::Migration),
+            Box::new(m20261019_000003_saved_queries::Migration),
+            Box::new(m20261019_000004_smart_notebooks::Migration),
+            Box::new(m20261019_000005_note_attachments::Migration),
+            Box::new(m20261019_000006_note_status::Migration),
+        ]
     }
 }
@@ -0,0 +1,26 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // the first migration created `Notes.title` while the entity reads
+        // `note_name`; databases made before that was noticed already have the
+        // right name, so only rename when the old column is there
+        if !manager.has_column("Notes", "title").await? {
+            return Ok(());
+        }
+        manager
+            .alter_table(
+                Table::alter().table("Notes").rename_column("title", "note_name").to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // leave the column as the entity expects it
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{Statement, TransactionTrait};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+// set by `modnote doctor duplicates --merge` to merge records whose names only
+// differ in case or spacing instead of stopping the upgrade
+pub static MERGE_DUPLICATES: AtomicBool = AtomicBool::new(false);
+
+// (table, name column) of every named table; names are unique ignoring case
+const NAMED: [(&str, &str); 3] =
+    [("Notebooks", "notebook_name"), ("Notes", "note_name"), ("Tags", "tag_name")];
+
+// (join table, column, referenced table, name column) of every join column
+const JOINS: [(&str, &str, &str, &str); 4] = [
+    ("NotebookNotes", "notebook_id", "Notebooks", "notebook_name"),
+    ("NotebookNotes", "note_id", "Notes", "note_name"),
+    ("NoteTags", "note_id", "Notes", "note_name"),
+    ("NoteTags", "tag_id", "Tags", "tag_name"),
+];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // names that would collide under the unique index; merging them loses
+        // data, so only do it when asked to
+        let duplicates = find_duplicates(db).await?;
+        if !duplicates.is_empty() && !MERGE_DUPLICATES.load(Ordering::Relaxed) {
+            return Err(DbErr::Migration(format!(
+                "names are now unique ignoring case, but these records share a name:\n  {}\n\
+                 run `modnote doctor duplicates --merge` to merge each into the oldest record \
+                 with its name, or rename them with sqlite3",
+                duplicates.join("\n  ")
+            )));
+        }
+
+        let txn = db.begin().await?;
+        // store names normalized, so "a  b " and "a b" are caught by the index
+        for (table, name) in NAMED {
+            for (id, value) in names(&txn, table, name).await? {
+                let normalized = normalize(&value);
+                if normalized != value {
+                    txn.execute_raw(Statement::from_sql_and_values(
+                        txn.get_database_backend(),
+                        format!(r#"UPDATE "{table}" SET "{name}" = ? WHERE "id" = ?"#),
+                        [normalized.into(), id.into()],
+                    ))
+                    .await?;
+                }
+            }
+        }
+        if !duplicates.is_empty() {
+            merge(&txn).await?;
+        }
+        // a membership listed twice says nothing more than once
+        for (join, left, right) in
+            [("NotebookNotes", "notebook_id", "note_id"), ("NoteTags", "note_id", "tag_id")]
+        {
+            txn.execute_unprepared(&format!(
+                r#"DELETE FROM "{join}" WHERE "id" NOT IN (
+                    SELECT MIN("id") FROM "{join}" GROUP BY "{left}", "{right}")"#
+            ))
+            .await?;
+        }
+        txn.commit().await?;
+
+        // sea-query can't express a collation on an index column, so use raw sql here
+        for (table, name) in NAMED {
+            db.execute_unprepared(&format!(
+                r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx-{lower}-{name}" ON "{table}" ("{name}" COLLATE NOCASE)"#,
+                lower = table.to_lowercase()
+            ))
+            .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notebooknotes-notebook_id-note_id")
+                    .table("NotebookNotes")
+                    .col("notebook_id")
+                    .col("note_id")
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notetags-note_id-tag_id")
+                    .table("NoteTags")
+                    .col("note_id")
+                    .col("tag_id")
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (table, name) in NAMED {
+            let index = format!("idx-{}-{}", table.to_lowercase(), name);
+            manager.drop_index(Index::drop().name(index).table(table).to_owned()).await?;
+        }
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-notebooknotes-notebook_id-note_id")
+                    .table("NotebookNotes")
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop().name("idx-notetags-note_id-tag_id").table("NoteTags").to_owned(),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+// the same as modnote's `names::normalize`: trimmed, with runs of whitespace
+// collapsed; names are stored in this form from now on
+fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// (id, name) of every row of `table`, oldest first
+async fn names<C: ConnectionTrait>(
+    db: &C,
+    table: &str,
+    name: &str,
+) -> Result<Vec<(i64, String)>, DbErr> {
+    let sql = format!(r#"SELECT "id", "{name}" FROM "{table}" ORDER BY "id""#);
+    let rows = db.query_all_raw(Statement::from_string(db.get_database_backend(), sql)).await?;
+    rows.iter().map(|row| Ok((row.try_get_by_index(0)?, row.try_get_by_index(1)?))).collect()
+}
+
+// one line per group of names the unique index would reject once normalized,
+// e.g. `notes 'Vec', 'vec ' (ids 1, 4)`; only ascii letters are folded, like the
+// NOCASE collation of the index
+async fn find_duplicates<C: ConnectionTrait>(db: &C) -> Result<Vec<String>, DbErr> {
+    let mut lines = Vec::new();
+    for (table, name) in NAMED {
+        let mut groups: BTreeMap<String, Vec<(i64, String)>> = BTreeMap::new();
+        for (id, value) in names(db, table, name).await? {
+            groups.entry(normalize(&value).to_ascii_lowercase()).or_default().push((id, value));
+        }
+        for group in groups.values().filter(|group| group.len() > 1) {
+            let names: Vec<String> =
+                group.iter().map(|(_, value)| format!("'{}'", value)).collect();
+            let ids: Vec<String> = group.iter().map(|(id, _)| id.to_string()).collect();
+            lines.push(format!(
+                "{} {} (ids {})",
+                table.to_lowercase(),
+                names.join(", "),
+                ids.join(", ")
+            ));
+        }
+    }
+    Ok(lines)
+}
+
+// merge each duplicate into the record with the lowest id and the same name: note
+// contents are appended and memberships moved over before the duplicates go
+async fn merge<C: ConnectionTrait>(db: &C) -> Result<(), DbErr> {
+    db.execute_unprepared(
+        r#"UPDATE "Notes" SET "content" = "content" || char(10) || char(10) || (
+            SELECT group_concat(d."content", char(10) || char(10)) FROM "Notes" d
+            WHERE d."note_name" = "Notes"."note_name" COLLATE NOCASE AND d."id" > "Notes"."id")
+        WHERE "id" IN (
+            SELECT MIN("id") FROM "Notes" GROUP BY "note_name" COLLATE NOCASE HAVING COUNT(*) > 1)"#,
+    )
+    .await?;
+
+    for (join, column, table, name) in JOINS {
+        db.execute_unprepared(&format!(
+            r#"UPDATE "{join}" SET "{column}" = (
+                SELECT MIN(k."id") FROM "{table}" k JOIN "{table}" d
+                ON k."{name}" = d."{name}" COLLATE NOCASE WHERE d."id" = "{join}"."{column}")
+            WHERE "{column}" IN (SELECT "id" FROM "{table}")"#
+        ))
+        .await?;
+    }
+
+    for (table, name) in NAMED {
+        db.execute_unprepared(&format!(
+            r#"DELETE FROM "{table}" WHERE "id" NOT IN (
+                SELECT MIN("id") FROM "{table}" GROUP BY "{name}" COLLATE NOCASE)"#
+        ))
+        .await?;
+    }
+    Ok(())
+}
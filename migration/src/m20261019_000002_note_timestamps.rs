@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const COLUMNS: [&str; 2] = ["created_at", "updated_at"];
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // sqlite only adds columns with a constant default, so existing notes are
+        // stamped with the time of the migration afterwards
+        for column in COLUMNS {
+            db.execute_unprepared(&format!(
+                r#"ALTER TABLE "Notes" ADD COLUMN "{column}" datetime_text NOT NULL DEFAULT '1970-01-01 00:00:00'"#
+            ))
+            .await?;
+            db.execute_unprepared(&format!(r#"UPDATE "Notes" SET "{column}" = CURRENT_TIMESTAMP"#))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in COLUMNS {
+            manager
+                .alter_table(Table::alter().table("Notes").drop_column(column).to_owned())
+                .await?;
+        }
+        Ok(())
+    }
+}
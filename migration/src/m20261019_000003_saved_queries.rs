@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // create saved queries table
+        manager
+            .create_table(
+                Table::create()
+                    .table("SavedQueries")
+                    .if_not_exists()
+                    .col(pk_auto("id"))
+                    .col(string("query_name"))
+                    .col(string("query"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx-savedqueries-query_name" ON "SavedQueries" ("query_name" COLLATE NOCASE)"#,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table("SavedQueries").to_owned()).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,22 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // a smart notebook stores the filter its notes are found with
+        manager
+            .alter_table(
+                Table::alter().table("Notebooks").add_column(text_null("filter")).to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table("Notebooks").drop_column("filter").to_owned())
+            .await
+    }
+}
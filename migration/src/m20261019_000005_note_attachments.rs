@@ -0,0 +1,48 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // create the attachments table: Note -> Attachments (note has many
+        // attachments), the file contents stored in the database with them
+        manager
+            .create_table(
+                Table::create()
+                    .table("NoteAttachments")
+                    .if_not_exists()
+                    .col(pk_auto("id"))
+                    .col(integer("note_id"))
+                    .col(string("file_name"))
+                    .col(string("mime_type"))
+                    .col(big_integer("size"))
+                    .col(string("hash"))
+                    .col(blob("data"))
+                    .col(date_time("created_at"))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-noteattachments-note_id")
+                            .from(Alias::new("NoteAttachments"), Alias::new("note_id"))
+                            .to(Alias::new("Notes"), Alias::new("id"))
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"CREATE UNIQUE INDEX IF NOT EXISTS "idx-noteattachments-note_id-file_name" ON "NoteAttachments" ("note_id", "file_name" COLLATE NOCASE)"#,
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table("NoteAttachments").to_owned()).await?;
+        Ok(())
+    }
+}
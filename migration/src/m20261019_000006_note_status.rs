@@ -0,0 +1,56 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // how far a note has been studied; existing notes start out new
+        manager
+            .alter_table(
+                Table::alter()
+                    .table("Notes")
+                    .add_column(string("status").default("new"))
+                    .to_owned(),
+            )
+            .await?;
+
+        // every change of status: Note -> StatusHistory (note has many events)
+        manager
+            .create_table(
+                Table::create()
+                    .table("NoteStatusHistory")
+                    .if_not_exists()
+                    .col(pk_auto("id"))
+                    .col(integer("note_id"))
+                    .col(string("from_status"))
+                    .col(string("to_status"))
+                    .col(date_time("changed_at"))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-notestatushistory-note_id")
+                            .from(Alias::new("NoteStatusHistory"), Alias::new("note_id"))
+                            .to(Alias::new("Notes"), Alias::new("id"))
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-notestatushistory-changed_at")
+                    .table("NoteStatusHistory")
+                    .col("changed_at")
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table("NoteStatusHistory").to_owned()).await?;
+        manager.alter_table(Table::alter().table("Notes").drop_column("status").to_owned()).await
+    }
+}
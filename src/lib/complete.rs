@@ -0,0 +1,62 @@
+// shell completion of notebook, note and tag names, read from the database when
+// the shell asks for candidates, and of template names
+use crate::config::{self, Config};
+use crate::crud::{note::get_all_notes, notebook::get_all_notebooks, tag::get_all_tags};
+use crate::templates;
+use clap_complete::CompletionCandidate;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
+use std::future::Future;
+use std::path::PathBuf;
+
+pub fn notebooks() -> Vec<CompletionCandidate> {
+    names(|db| async move {
+        Ok(get_all_notebooks(&db).await?.into_iter().map(|b| b.notebook_name).collect())
+    })
+}
+
+pub fn notes() -> Vec<CompletionCandidate> {
+    names(
+        |db| async move { Ok(get_all_notes(&db).await?.into_iter().map(|n| n.note_name).collect()) },
+    )
+}
+
+pub fn tags() -> Vec<CompletionCandidate> {
+    names(
+        |db| async move { Ok(get_all_tags(&db).await?.into_iter().map(|t| t.tag_name).collect()) },
+    )
+}
+
+pub fn templates() -> Vec<CompletionCandidate> {
+    let templates = templates::list().unwrap_or_default();
+    templates.into_iter().map(|t| CompletionCandidate::new(t.name)).collect()
+}
+
+// run `query` against the configured database; completion happens inside main's
+// runtime, so the query gets a thread and runtime of its own, and any failure
+// just means no candidates
+fn names<F, Fut>(query: F) -> Vec<CompletionCandidate>
+where
+    F: FnOnce(DatabaseConnection) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<Vec<String>, DbErr>>,
+{
+    let names = std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().ok()?;
+        runtime.block_on(async {
+            let db = Database::connect(options()).await.ok()?;
+            query(db).await.ok()
+        })
+    })
+    .join()
+    .ok()
+    .flatten()
+    .unwrap_or_default();
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
+
+// a read-only connection, so completing never creates a database
+fn options() -> ConnectOptions {
+    let path = Config::load().ok().and_then(|c| c.db).unwrap_or(PathBuf::from(config::DEFAULT_DB));
+    let mut opt = ConnectOptions::new(format!("sqlite://{}?mode=ro", path.display()));
+    opt.max_connections(1).sqlx_logging(false);
+    opt
+}
@@ -0,0 +1,223 @@
+// user settings from ~/.config/modnote/config.toml; command-line flags override them
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+pub const DEFAULT_DB: &str = "db.sqlite";
+pub const DEFAULT_MAX_CONTENT_BYTES: u64 = 1024 * 1024;
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 10 * 1024 * 1024;
+
+// the keys `modnote config` knows besides `aliases.<name>`
+const KEYS: [&str; 8] = [
+    "db",
+    "default_notebook",
+    "output",
+    "editor",
+    "pager",
+    "color",
+    "max_content_bytes",
+    "max_attachment_bytes",
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Output {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Color {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    // path of the sqlite database
+    pub db: Option<PathBuf>,
+    // notebook new notes are filed in when none is given
+    pub default_notebook: Option<String>,
+    pub output: Output,
+    pub editor: Option<String>,
+    pub pager: Option<String>,
+    pub color: Color,
+    // largest note content read from a file or stdin
+    pub max_content_bytes: Option<u64>,
+    // largest file attached to a note
+    pub max_attachment_bytes: Option<u64>,
+    // commands of their own, e.g. `stale = "find 'updated<30d'"`
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Config {
+    // the config file, or the defaults when there is none
+    pub fn load() -> Result<Config> {
+        let Some(path) = path() else {
+            return Ok(Config::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text)
+                .with_context(|| format!("invalid config file {}", path.display())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e).with_context(|| format!("can't read {}", path.display())),
+        }
+    }
+
+    // every setting with its value in effect, aliases as `aliases.<name>`
+    pub fn list(&self) -> Vec<(String, String)> {
+        let settings = [
+            ("db", self.db.as_ref().map_or(DEFAULT_DB.into(), |p| p.display().to_string())),
+            ("default_notebook", self.default_notebook.clone().unwrap_or_default()),
+            ("output", value_name(self.output)),
+            ("editor", self.editor.clone().unwrap_or_default()),
+            ("pager", self.pager.clone().unwrap_or_default()),
+            ("color", value_name(self.color)),
+            ("max_content_bytes", self.max_content_bytes().to_string()),
+            ("max_attachment_bytes", self.max_attachment_bytes().to_string()),
+        ];
+        let mut entries: Vec<(String, String)> =
+            settings.into_iter().map(|(key, value)| (key.to_string(), value)).collect();
+        for (name, command) in &self.aliases {
+            entries.push((format!("aliases.{}", name), command.clone()));
+        }
+        entries
+    }
+
+    pub fn get(&self, key: &str) -> Result<String> {
+        if let Some((_, value)) = self.list().into_iter().find(|(k, _)| k == key) {
+            return Ok(value);
+        }
+        match key.strip_prefix("aliases.") {
+            Some(name) => bail!("no alias named '{}'", name),
+            None => bail!("unknown config key '{}', expected {} or aliases.<name>", key, keys()),
+        }
+    }
+
+    pub fn max_content_bytes(&self) -> u64 {
+        self.max_content_bytes.unwrap_or(DEFAULT_MAX_CONTENT_BYTES)
+    }
+
+    pub fn max_attachment_bytes(&self) -> u64 {
+        self.max_attachment_bytes.unwrap_or(DEFAULT_MAX_ATTACHMENT_BYTES)
+    }
+
+    // replace an alias in `args[1]` with the words of its command; built-in
+    // commands can't be shadowed
+    pub fn expand_alias(&self, args: &mut Vec<String>, is_command: impl Fn(&str) -> bool) {
+        let Some(name) = args.get(1).filter(|name| !is_command(name)) else {
+            return;
+        };
+        if let Some(command) = self.aliases.get(name) {
+            let words = split_words(command);
+            args.splice(1..2, words);
+        }
+    }
+}
+
+// store `value` under `key` in the config file, keeping the other settings; an
+// empty value removes the key
+pub fn set(key: &str, value: &str) -> Result<PathBuf> {
+    let path = path().context("can't locate the config file, set $HOME or $MODNOTE_CONFIG")?;
+    let mut table: toml::Table = match fs::read_to_string(&path) {
+        Ok(text) => toml::from_str(&text)
+            .with_context(|| format!("invalid config file {}", path.display()))?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => toml::Table::new(),
+        Err(e) => return Err(e).with_context(|| format!("can't read {}", path.display())),
+    };
+
+    let (target, name) = match key.split_once('.') {
+        Some(("aliases", name)) if !name.is_empty() => {
+            let aliases = table
+                .entry("aliases")
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .context("aliases in the config file is not a table")?;
+            (aliases, name)
+        }
+        _ if KEYS.contains(&key) => (&mut table, key),
+        _ => bail!("unknown config key '{}', expected {} or aliases.<name>", key, keys()),
+    };
+    match value {
+        "" => target.remove(name),
+        // sizes are stored as numbers, everything else as text
+        value if key.starts_with("max_") => {
+            let bytes = value
+                .parse::<i64>()
+                .with_context(|| format!("invalid value '{}' for {}", value, key))?;
+            target.insert(name.to_string(), toml::Value::Integer(bytes))
+        }
+        value => target.insert(name.to_string(), toml::Value::String(value.to_string())),
+    };
+
+    // make sure the file still loads before replacing it
+    let text = toml::to_string(&table)?;
+    toml::from_str::<Config>(&text)
+        .with_context(|| format!("invalid value '{}' for {}", value, key))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+// where the config file lives: $MODNOTE_CONFIG, else modnote/config.toml under
+// $XDG_CONFIG_HOME or ~/.config
+pub fn path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os("MODNOTE_CONFIG") {
+        return Some(path.into());
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("modnote").join("config.toml"))
+}
+
+fn keys() -> String {
+    KEYS.join(", ")
+}
+
+fn value_name(value: impl ValueEnum) -> String {
+    value.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
+}
+
+// split a command line into words, keeping quoted text together
+pub fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
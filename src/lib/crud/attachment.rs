@@ -0,0 +1,118 @@
+use crate::entities::note_attachments::{self};
+use crate::entities::prelude::NoteAttachments;
+use sea_orm::sea_query::{Expr, ExprTrait, Func};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, FromQueryResult, QueryFilter, QueryOrder,
+    QuerySelect, Select,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// an attachment without its data, for listing; load the data of one at a time
+// with `get_attachment_data`
+#[derive(Clone, Debug, FromQueryResult, Serialize)]
+pub struct AttachmentInfo {
+    pub id: i64,
+    pub note_id: i64,
+    pub file_name: String,
+    pub mime_type: String,
+    pub size: i64,
+    pub hash: String,
+    pub created_at: sea_orm::prelude::DateTime,
+}
+
+// CRUD: Create
+// attach `data` to a note under `file_name`, with its mime type guessed from the
+// name, its size and its hash
+pub async fn add_attachment<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    file_name: String,
+    data: Vec<u8>,
+) -> Result<note_attachments::Model, DbErr> {
+    match get_attachment(db, note_id, &file_name).await {
+        Ok(existing) => {
+            return Err(DbErr::Custom(format!(
+                "Note already has an attachment named {}",
+                existing.file_name
+            )))
+        }
+        Err(DbErr::RecordNotFound(_)) => {}
+        Err(err) => return Err(err),
+    }
+    let mime_type = mime_guess::from_path(&file_name).first_or_octet_stream().to_string();
+    let attachment = note_attachments::ActiveModel {
+        id: NotSet,
+        note_id: Set(note_id),
+        file_name: Set(file_name),
+        mime_type: Set(mime_type),
+        size: Set(data.len() as i64),
+        hash: Set(hash(&data)),
+        data: Set(data),
+        created_at: NotSet,
+    };
+
+    attachment.insert(db).await
+}
+
+// CRUD: Read (Get)
+pub async fn get_attachments_for_note<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+) -> Result<Vec<AttachmentInfo>, DbErr> {
+    find_info()
+        .filter(note_attachments::Column::NoteId.eq(note_id))
+        .order_by_asc(note_attachments::Column::FileName)
+        .into_model::<AttachmentInfo>()
+        .all(db)
+        .await
+}
+
+// lookup by file name within a note, ignoring case
+pub async fn get_attachment<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    file_name: &str,
+) -> Result<AttachmentInfo, DbErr> {
+    find_info()
+        .filter(note_attachments::Column::NoteId.eq(note_id))
+        .filter(
+            Expr::expr(Func::lower(Expr::col(note_attachments::Column::FileName)))
+                .eq(file_name.trim().to_ascii_lowercase()),
+        )
+        .into_model::<AttachmentInfo>()
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Attachment with name {} not found", file_name)))
+}
+
+// the data of the attachment with `id`
+pub async fn get_attachment_data<C: ConnectionTrait>(db: &C, id: i64) -> Result<Vec<u8>, DbErr> {
+    NoteAttachments::find_by_id(id)
+        .select_only()
+        .column(note_attachments::Column::Data)
+        .into_tuple::<Vec<u8>>()
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Attachment with id {} not found", id)))
+}
+
+// every column but the data
+fn find_info() -> Select<NoteAttachments> {
+    NoteAttachments::find().select_only().columns([
+        note_attachments::Column::Id,
+        note_attachments::Column::NoteId,
+        note_attachments::Column::FileName,
+        note_attachments::Column::MimeType,
+        note_attachments::Column::Size,
+        note_attachments::Column::Hash,
+        note_attachments::Column::CreatedAt,
+    ])
+}
+
+// sha-256 of `data`, hex encoded
+pub fn hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
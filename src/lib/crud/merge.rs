@@ -0,0 +1,171 @@
+use crate::crud::{
+    attachment::get_attachments_for_note, note::get_note_by_id, notebook::get_notebook_by_id,
+    tag::get_tag_by_id,
+};
+use crate::entities::prelude::{
+    NoteStatusHistory, NoteTags, NotebookNotes, Notebooks, Notes, Tags,
+};
+use crate::entities::{
+    note_attachments, note_status_history, note_tags, notebook_notes, notebooks, notes, tags,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DbErr,
+    EntityTrait, QueryFilter, QuerySelect,
+};
+
+// CRUD: Merge
+// merge notebook `other_id` into `keep_id`, moving its notes over; a smart
+// notebook has no filed notes to move and a filter that can't be combined, so
+// merges involving one are refused
+pub async fn merge_notebooks<C: ConnectionTrait>(
+    db: &C,
+    keep_id: i64,
+    other_id: i64,
+) -> Result<notebooks::Model, DbErr> {
+    let keep = get_notebook_by_id(db, keep_id).await?;
+    let other = get_notebook_by_id(db, other_id).await?;
+    if let Some(smart) = [&keep, &other].into_iter().find(|book| book.filter.is_some()) {
+        return Err(DbErr::Custom(format!(
+            "Notebook {} is a smart notebook, only regular notebooks can be merged",
+            smart.notebook_name
+        )));
+    }
+
+    repoint(
+        db,
+        NotebookNotes::find().filter(notebook_notes::Column::NotebookId.eq(keep_id)),
+        notebook_notes::Column::NotebookId,
+        notebook_notes::Column::NoteId,
+        keep_id,
+        other_id,
+    )
+    .await?;
+    Notebooks::delete_by_id(other_id).exec(db).await?;
+
+    if keep.description.is_empty() && !other.description.is_empty() {
+        let mut keep: notebooks::ActiveModel = keep.into();
+        keep.description = Set(other.description);
+        return keep.update(db).await;
+    }
+    Ok(keep)
+}
+
+// merge tag `other_id` into `keep_id`, retagging its notes
+pub async fn merge_tags<C: ConnectionTrait>(
+    db: &C,
+    keep_id: i64,
+    other_id: i64,
+) -> Result<tags::Model, DbErr> {
+    let keep = get_tag_by_id(db, keep_id).await?;
+    get_tag_by_id(db, other_id).await?;
+
+    repoint(
+        db,
+        NoteTags::find().filter(note_tags::Column::TagId.eq(keep_id)),
+        note_tags::Column::TagId,
+        note_tags::Column::NoteId,
+        keep_id,
+        other_id,
+    )
+    .await?;
+    Tags::delete_by_id(other_id).exec(db).await?;
+    Ok(keep)
+}
+
+// merge note `other_id` into `keep_id`: the content is appended, the tags and
+// notebook memberships are unioned and the attachments and status history moved
+// over
+pub async fn merge_notes<C: ConnectionTrait>(
+    db: &C,
+    keep_id: i64,
+    other_id: i64,
+) -> Result<notes::Model, DbErr> {
+    let keep = get_note_by_id(db, keep_id).await?;
+    let other = get_note_by_id(db, other_id).await?;
+
+    repoint(
+        db,
+        NoteTags::find().filter(note_tags::Column::NoteId.eq(keep_id)),
+        note_tags::Column::NoteId,
+        note_tags::Column::TagId,
+        keep_id,
+        other_id,
+    )
+    .await?;
+    repoint(
+        db,
+        NotebookNotes::find().filter(notebook_notes::Column::NoteId.eq(keep_id)),
+        notebook_notes::Column::NoteId,
+        notebook_notes::Column::NotebookId,
+        keep_id,
+        other_id,
+    )
+    .await?;
+    move_attachments(db, keep_id, other_id).await?;
+    NoteStatusHistory::update_many()
+        .col_expr(note_status_history::Column::NoteId, Expr::value(keep_id))
+        .filter(note_status_history::Column::NoteId.eq(other_id))
+        .exec(db)
+        .await?;
+    Notes::delete_by_id(other_id).exec(db).await?;
+
+    let content = match (keep.content.trim_end(), other.content.trim()) {
+        (kept, "") => kept.to_string(),
+        ("", merged) => merged.to_string(),
+        (kept, merged) => format!("{}\n\n{}", kept, merged),
+    };
+    let mut keep: notes::ActiveModel = keep.into();
+    keep.content = Set(content);
+    keep.update(db).await
+}
+
+// move the join rows of `other_id` over to `keep_id`, dropping the ones `keep_id`
+// already has so the unique index on the join table holds
+async fn repoint<C, E, Col>(
+    db: &C,
+    kept_rows: sea_orm::Select<E>,
+    owner: Col,
+    target: Col,
+    keep_id: i64,
+    other_id: i64,
+) -> Result<(), DbErr>
+where
+    C: ConnectionTrait,
+    E: EntityTrait<Column = Col>,
+    Col: ColumnTrait,
+{
+    let existing: Vec<i64> = kept_rows.select_only().column(target).into_tuple().all(db).await?;
+    E::delete_many().filter(owner.eq(other_id)).filter(target.is_in(existing)).exec(db).await?;
+    E::update_many()
+        .col_expr(owner, Expr::value(keep_id))
+        .filter(owner.eq(other_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+// move the attachments of note `other_id` over to `keep_id`; one whose name is
+// taken there is dropped when it holds the same data and renamed otherwise
+async fn move_attachments<C: ConnectionTrait>(
+    db: &C,
+    keep_id: i64,
+    other_id: i64,
+) -> Result<(), DbErr> {
+    let kept = get_attachments_for_note(db, keep_id).await?;
+    for attachment in get_attachments_for_note(db, other_id).await? {
+        let clash = kept.iter().find(|a| a.file_name.eq_ignore_ascii_case(&attachment.file_name));
+        let file_name = match clash {
+            Some(kept) if kept.hash == attachment.hash => continue,
+            Some(_) => format!("{}-{}", other_id, attachment.file_name),
+            None => attachment.file_name.clone(),
+        };
+        let attachment = note_attachments::ActiveModel {
+            id: Set(attachment.id),
+            note_id: Set(keep_id),
+            file_name: Set(file_name),
+            ..Default::default()
+        };
+        attachment.update(db).await?;
+    }
+    Ok(())
+}
@@ -1,3 +1,150 @@
+use crate::fuzzy;
+use sea_orm::{
+    ConnectionTrait, DbErr, EntityTrait, Order, PaginatorTrait, QueryOrder, QuerySelect, Select,
+};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod attachment;
+pub mod merge;
 pub mod note;
 pub mod notebook;
+pub mod query;
+pub mod split;
+pub mod status;
 pub mod tag;
+
+// keep the fuzzy matches of `query` among `items`, best first, or fail with the
+// closest names as suggestions
+pub(crate) fn fuzzy_matches<T>(
+    kind: &str,
+    field: &str,
+    query: &str,
+    mut items: Vec<T>,
+    name: impl Fn(&T) -> &str,
+) -> Result<Vec<T>, DbErr> {
+    let ranked = fuzzy::rank(query, items.iter().map(&name));
+    if ranked.is_empty() {
+        let suggestions = fuzzy::closest(query, items.iter().map(&name), 3);
+        let mut message = format!("{} with {} '{}' not found", kind, field, query);
+        if !suggestions.is_empty() {
+            message.push_str(&format!(". Did you mean: {}?", suggestions.join(", ")));
+        }
+        return Err(DbErr::RecordNotFound(message));
+    }
+    let mut slots: Vec<Option<T>> = items.drain(..).map(Some).collect();
+    Ok(ranked.into_iter().filter_map(|i| slots[i].take()).collect())
+}
+
+// what to order a listing by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Id,
+    Name,
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "id" => Ok(SortKey::Id),
+            "name" | "title" => Ok(SortKey::Name),
+            _ => Err(format!("unknown sort key '{}', expected id or name", s)),
+        }
+    }
+}
+
+// which slice of a listing to load
+#[derive(Clone, Copy, Debug)]
+pub struct ListOptions {
+    pub limit: u64,
+    pub offset: u64,
+    pub sort: SortKey,
+    pub descending: bool,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        ListOptions { limit: 20, offset: 0, sort: SortKey::Id, descending: false }
+    }
+}
+
+// one page of a listing with the totals needed for a footer
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub number: u64,
+    pub pages: u64,
+    pub total: u64,
+}
+
+impl<T> fmt::Display for Page<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // an offset past the last item has no page to name
+        if self.number > self.pages.max(1) {
+            return write!(f, "no results past item {}", self.total);
+        }
+        write!(f, "page {} of {} ({} total)", self.number, self.pages.max(1), self.total)
+    }
+}
+
+// load the page of `select` described by `options`, counting the rows through the
+// paginator so only one page is ever read
+pub(crate) async fn paginate<C, E>(
+    db: &C,
+    select: Select<E>,
+    id: E::Column,
+    name: E::Column,
+    options: &ListOptions,
+) -> Result<Page<E::Model>, DbErr>
+where
+    C: ConnectionTrait,
+    E: EntityTrait,
+    E::Model: Sync,
+{
+    let limit = options.limit.max(1);
+    let order = if options.descending { Order::Desc } else { Order::Asc };
+    let select = match options.sort {
+        SortKey::Id => select.order_by(id, order),
+        SortKey::Name => select.order_by(name, order.clone()).order_by(id, order),
+    };
+
+    let paginator = select.clone().paginate(db, limit);
+    let totals = paginator.num_items_and_pages().await?;
+    // an offset on a page boundary is read through the paginator, any other
+    // offset starts the page part-way
+    let items = if options.offset.is_multiple_of(limit) {
+        paginator.fetch_page(options.offset / limit).await?
+    } else {
+        select.offset(options.offset).limit(limit).all(db).await?
+    };
+    Ok(Page {
+        items,
+        number: options.offset / limit + 1,
+        pages: totals.number_of_pages,
+        total: totals.number_of_items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Page;
+
+    fn page(number: u64, pages: u64, total: u64) -> Page<()> {
+        Page { items: Vec::new(), number, pages, total }
+    }
+
+    #[test]
+    fn footer_names_the_page() {
+        assert_eq!(page(2, 3, 50).to_string(), "page 2 of 3 (50 total)");
+        assert_eq!(page(1, 0, 0).to_string(), "page 1 of 1 (0 total)");
+    }
+
+    #[test]
+    fn footer_past_the_end_says_so() {
+        assert_eq!(page(6, 1, 3).to_string(), "no results past item 3");
+    }
+}
@@ -1,33 +1,86 @@
+use crate::crud::{
+    notebook::add_note_to_notebook, paginate, query::expand, tag::*, ListOptions, Page,
+};
 use crate::entities::notes::{self};
 use crate::entities::prelude::Notes;
+use crate::query::{self, Filter};
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
-    ColumnTrait, DbConn, DbErr, EntityTrait, ModelTrait, QueryFilter,
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter,
 };
-use std::io;
 
 // CRUD: Create
-pub async fn create_note(
-    db: &DbConn,
+pub async fn create_note<C: ConnectionTrait>(
+    db: &C,
     note_name: String,
     content: String,
 ) -> Result<notes::Model, DbErr> {
-    let new_note =
-        notes::ActiveModel { id: NotSet, note_name: Set(note_name), content: Set(content) };
+    let new_note = notes::ActiveModel {
+        id: NotSet,
+        note_name: Set(note_name),
+        content: Set(content),
+        ..Default::default()
+    };
 
     // create relationship between note and notebook
 
-
     new_note.insert(db).await
 }
 
+// create a note filed in the given notebooks and tagged with the given tags,
+// creating missing tags; run it on a transaction to make it all-or-nothing
+pub async fn create_note_with_links<C: ConnectionTrait>(
+    db: &C,
+    note_name: String,
+    content: String,
+    notebook_ids: &[i64],
+    tag_names: &[String],
+) -> Result<notes::Model, DbErr> {
+    let note = create_note(db, note_name, content).await?;
+    for notebook_id in notebook_ids {
+        add_note_to_notebook(db, *notebook_id, note.id).await?;
+    }
+    // `--tag b --tag B` names one tag, which is linked once
+    let mut tag_ids = Vec::new();
+    for name in tag_names {
+        let tag = get_or_create_tag(db, name.to_owned()).await?;
+        if !tag_ids.contains(&tag.id) {
+            tag_ids.push(tag.id);
+            add_tag_to_note(db, note.id, tag.id).await?;
+        }
+    }
+    Ok(note)
+}
+
 // CRUD: Read (Get)
-pub async fn get_all_notes(db: &DbConn) -> Result<Vec<notes::Model>, DbErr> {
+pub async fn get_all_notes<C: ConnectionTrait>(db: &C) -> Result<Vec<notes::Model>, DbErr> {
     Notes::find().all(db).await
 }
 
-pub async fn get_note_by_title(db: &DbConn, title: String) -> Result<notes::Model, DbErr> {
+// one page of notes, sorted and limited by `options`
+pub async fn list_notes<C: ConnectionTrait>(
+    db: &C,
+    options: &ListOptions,
+) -> Result<Page<notes::Model>, DbErr> {
+    paginate(db, Notes::find(), notes::Column::Id, notes::Column::NoteName, options).await
+}
+
+// one page of the notes matching `filter`
+pub async fn filter_notes<C: ConnectionTrait>(
+    db: &C,
+    filter: &Filter,
+    options: &ListOptions,
+) -> Result<Page<notes::Model>, DbErr> {
+    let filter = expand(db, filter).await?;
+    let select = Notes::find().filter(query::compile(&filter));
+    paginate(db, select, notes::Column::Id, notes::Column::NoteName, options).await
+}
+
+pub async fn get_note_by_title<C: ConnectionTrait>(
+    db: &C,
+    title: String,
+) -> Result<notes::Model, DbErr> {
     Notes::find()
         .filter(notes::Column::NoteName.eq(title.to_owned()))
         .one(db)
@@ -35,50 +88,43 @@ pub async fn get_note_by_title(db: &DbConn, title: String) -> Result<notes::Mode
         .ok_or(DbErr::RecordNotFound(format!("Note with title {} not found", title)))
 }
 
-// CRUD: Update
-pub async fn update_note_by_title(db: &DbConn, title: Option<String>) -> Result<(), DbErr> {
-    // Find the note by title
-    let note: Option<notes::Model> = Notes::find()
-        .filter(notes::Column::NoteName.eq(title.to_owned().unwrap_or_default()))
-        .one(db)
-        .await
-        .unwrap();
-    // Create an active model from the found note
-    let mut note: notes::ActiveModel = note.unwrap().into();
-
-    // Get new title from user input and update the active model
-    println!("Enter new title for note:");
-    let mut new_title = String::new();
-    io::stdin().read_line(&mut new_title).expect("Failed to read line");
-    let new_title = new_title.trim().to_string();
-    note.note_name = Set(new_title);
+// case-insensitive fuzzy lookup by title, best match first
+pub async fn find_notes_by_title<C: ConnectionTrait>(
+    db: &C,
+    title: &str,
+) -> Result<Vec<notes::Model>, DbErr> {
+    let notes = Notes::find().all(db).await?;
+    super::fuzzy_matches("Note", "title", title, notes, |n| &n.note_name)
+}
 
-    // Get new content from user input and update the active model
-    let mut new_content = String::new();
-    println!("Enter new content for note:");
-    io::stdin().read_line(&mut new_content).expect("Failed to read line");
-    let new_content = new_content.trim().to_string();
-    note.content = Set(new_content);
+pub async fn get_note_by_id<C: ConnectionTrait>(db: &C, id: i64) -> Result<notes::Model, DbErr> {
+    Notes::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Note with id {} not found", id)))
+}
 
-    // Save the updated note back to the database
-    note.update(db).await.unwrap();
+// CRUD: Update
+pub async fn update_note<C: ConnectionTrait>(
+    db: &C,
+    id: i64,
+    title: String,
+    content: String,
+) -> Result<notes::Model, DbErr> {
+    let mut note: notes::ActiveModel = get_note_by_id(db, id).await?.into();
+    note.note_name = Set(title);
+    note.content = Set(content);
 
-    Ok(())
+    note.update(db).await
 }
 
 // CRUD: Delete
-pub async fn delete_note_by_title(db: &DbConn, title: &Option<String>) -> Result<String, DbErr> {
-    let note: Option<notes::Model> = Notes::find()
-        .filter(notes::Column::NoteName.eq(title.to_owned().unwrap_or_default()))
-        .one(db)
-        .await?;
-    let note: notes::Model = note.unwrap();
-    note.delete(db).await?;
-    Ok("Successfully deleted note".to_string())
+pub async fn delete_note_by_id<C: ConnectionTrait>(db: &C, id: i64) -> Result<String, DbErr> {
+    let deleted = Notes::delete_by_id(id).exec(db).await?;
+    Ok(format!("Successfully deleted {} notes", deleted.rows_affected))
 }
 
-pub async fn delete_all_notes(db: &DbConn) -> Result<String, DbErr> {
+pub async fn delete_all_notes<C: ConnectionTrait>(db: &C) -> Result<String, DbErr> {
     let deleted = Notes::delete_many().filter(notes::Column::Id.gt(0)).exec(db).await?;
     Ok(format!("Successfully deleted {} notes", deleted.rows_affected))
 }
-
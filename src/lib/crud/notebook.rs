@@ -1,80 +1,175 @@
-use crate::entities::notebooks::{self};
-use crate::entities::prelude::Notebooks;
+use crate::crud::{
+    paginate,
+    query::{load_filter, load_filter_within},
+    ListOptions, Page,
+};
+use crate::entities::prelude::{NotebookNotes, Notebooks, Notes};
+use crate::entities::{
+    notebook_notes::{self},
+    notebooks::{self},
+    notes::{self},
+};
+use crate::query;
 use sea_orm::{
     ActiveModelTrait,
     ActiveValue::{NotSet, Set},
-    ColumnTrait, DbConn, DbErr, EntityTrait, ModelTrait, QueryFilter,
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect, RelationTrait,
 };
-use std::io;
 
 // CRUD: Create
-pub async fn create_notebook(
-    db: &DbConn,
+pub async fn create_notebook<C: ConnectionTrait>(
+    db: &C,
+    name: String,
+    desc: String,
+) -> Result<notebooks::Model, DbErr> {
+    let new_notebook = notebooks::ActiveModel {
+        id: NotSet,
+        notebook_name: Set(name),
+        description: Set(desc),
+        filter: NotSet,
+    };
+
+    new_notebook.insert(db).await
+}
+
+// create a smart notebook, holding the notes that match `filter` when it is read
+pub async fn create_smart_notebook<C: ConnectionTrait>(
+    db: &C,
     name: String,
     desc: String,
+    filter: String,
 ) -> Result<notebooks::Model, DbErr> {
-    let new_notebook =
-        notebooks::ActiveModel { id: NotSet, notebook_name: Set(name), description: Set(desc) };
+    // expanding as the notebook itself rejects a filter that refers back to it
+    load_filter_within(db, &filter, &[crate::names::fold(&name)]).await?;
+    let new_notebook = notebooks::ActiveModel {
+        id: NotSet,
+        notebook_name: Set(name),
+        description: Set(desc),
+        filter: Set(Some(filter)),
+    };
 
     new_notebook.insert(db).await
 }
 
+// create relationship between notebook and note
+pub async fn add_note_to_notebook<C: ConnectionTrait>(
+    db: &C,
+    notebook_id: i64,
+    note_id: i64,
+) -> Result<notebook_notes::Model, DbErr> {
+    let book = get_notebook_by_id(db, notebook_id).await?;
+    if book.filter.is_some() {
+        return Err(DbErr::Custom(format!(
+            "Notebook {} is a smart notebook, its notes come from its filter",
+            book.notebook_name
+        )));
+    }
+    let link = notebook_notes::ActiveModel {
+        id: NotSet,
+        notebook_id: Set(notebook_id),
+        note_id: Set(note_id),
+    };
+
+    link.insert(db).await
+}
+
 // CRUD: Read (Get)
-pub async fn get_all_notebooks(db: &DbConn) -> Result<Vec<notebooks::Model>, DbErr> {
+pub async fn get_all_notebooks<C: ConnectionTrait>(db: &C) -> Result<Vec<notebooks::Model>, DbErr> {
     Notebooks::find().all(db).await
 }
 
-pub async fn get_notebook_by_name(db: &DbConn, name: String) -> Result<notebooks::Model, DbErr> {
+// one page of notebooks, sorted and limited by `options`
+pub async fn list_notebooks<C: ConnectionTrait>(
+    db: &C,
+    options: &ListOptions,
+) -> Result<Page<notebooks::Model>, DbErr> {
+    let (id, name) = (notebooks::Column::Id, notebooks::Column::NotebookName);
+    paginate(db, Notebooks::find(), id, name, options).await
+}
+
+pub async fn get_notebook_by_name<C: ConnectionTrait>(
+    db: &C,
+    name: String,
+) -> Result<notebooks::Model, DbErr> {
     Notebooks::find()
         .filter(notebooks::Column::NotebookName.eq(name.to_owned()))
         .one(db)
         .await?
-        .ok_or(DbErr::RecordNotFound(format!("Notebook with id {} not found", name)))
+        .ok_or(DbErr::RecordNotFound(format!("Notebook with name {} not found", name)))
 }
 
-// CRUD: Update
-pub async fn update_notebook_by_name(db: &DbConn, name: Option<String>) -> Result<(), DbErr> {
-    // Find the notebook by name
-    let book: Option<notebooks::Model> = Notebooks::find()
-        .filter(notebooks::Column::NotebookName.eq(name.to_owned().unwrap_or_default()))
+// case-insensitive fuzzy lookup by name, best match first
+pub async fn find_notebooks_by_name<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+) -> Result<Vec<notebooks::Model>, DbErr> {
+    let notebooks = Notebooks::find().all(db).await?;
+    super::fuzzy_matches("Notebook", "name", name, notebooks, |b| &b.notebook_name)
+}
+
+pub async fn get_notebook_by_id<C: ConnectionTrait>(
+    db: &C,
+    id: i64,
+) -> Result<notebooks::Model, DbErr> {
+    Notebooks::find_by_id(id)
         .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Notebook with id {} not found", id)))
+}
+
+// the notes filed in a notebook, or for a smart notebook the notes its filter
+// matches right now
+pub async fn get_notes_in_notebook<C: ConnectionTrait>(
+    db: &C,
+    notebook_id: i64,
+) -> Result<Vec<notes::Model>, DbErr> {
+    let book = get_notebook_by_id(db, notebook_id).await?;
+    if let Some(filter) = &book.filter {
+        let filter = load_filter(db, filter).await?;
+        return Notes::find().filter(query::compile(&filter)).all(db).await;
+    }
+    Notes::find()
+        .join(sea_orm::JoinType::InnerJoin, notes::Relation::NotebookNotes.def())
+        .filter(notebook_notes::Column::NotebookId.eq(notebook_id))
+        .all(db)
         .await
-        .unwrap();
-    // Create an active model from the found notebook
-    let mut book: notebooks::ActiveModel = book.unwrap().into();
-
-    // Get new name from user input and update the active model
-    println!("Enter new name for notebook:");
-    let mut new_name = String::new();
-    io::stdin().read_line(&mut new_name).expect("Failed to read line");
-    let new_name = new_name.trim().to_string();
-    book.notebook_name = Set(new_name);
-
-    // Get new description from user input and update the active model
-    let mut new_desc = String::new();
-    println!("Enter new description for notebook:");
-    io::stdin().read_line(&mut new_desc).expect("Failed to read line");
-    let new_desc = new_desc.trim().to_string();
-    book.description = Set(new_desc);
-
-    // Save the updated notebook back to the database
-    book.update(db).await.unwrap();
-
-    Ok(())
+}
+
+// CRUD: Update
+pub async fn update_notebook<C: ConnectionTrait>(
+    db: &C,
+    id: i64,
+    name: String,
+    desc: String,
+) -> Result<notebooks::Model, DbErr> {
+    let mut book: notebooks::ActiveModel = get_notebook_by_id(db, id).await?.into();
+    book.notebook_name = Set(name);
+    book.description = Set(desc);
+
+    book.update(db).await
 }
 
 // CRUD: Delete
-pub async fn delete_notebook_by_name(db: &DbConn, name: &Option<String>) -> Result<String, DbErr> {
-    let book: Option<notebooks::Model> = Notebooks::find()
-        .filter(notebooks::Column::NotebookName.eq(name.to_owned().unwrap_or_default()))
-        .one(db)
+pub async fn delete_notebook_by_id<C: ConnectionTrait>(db: &C, id: i64) -> Result<String, DbErr> {
+    let deleted = Notebooks::delete_by_id(id).exec(db).await?;
+    Ok(format!("Successfully deleted {} notebooks", deleted.rows_affected))
+}
+
+// remove relationship between notebook and note
+pub async fn remove_note_from_notebook<C: ConnectionTrait>(
+    db: &C,
+    notebook_id: i64,
+    note_id: i64,
+) -> Result<u64, DbErr> {
+    let deleted = NotebookNotes::delete_many()
+        .filter(notebook_notes::Column::NotebookId.eq(notebook_id))
+        .filter(notebook_notes::Column::NoteId.eq(note_id))
+        .exec(db)
         .await?;
-    let book: notebooks::Model = book.unwrap();
-    book.delete(db).await?;
-    Ok("Successfully deleted notebook".to_string())
+    Ok(deleted.rows_affected)
 }
 
-pub async fn delete_all_notebooks(db: &DbConn) -> Result<String, DbErr> {
+pub async fn delete_all_notebooks<C: ConnectionTrait>(db: &C) -> Result<String, DbErr> {
     let deleted = Notebooks::delete_many().filter(notebooks::Column::Id.gt(0)).exec(db).await?;
     Ok(format!("Successfully deleted {} notebooks", deleted.rows_affected))
 }
@@ -0,0 +1,156 @@
+use crate::entities::prelude::{Notebooks, SavedQueries};
+use crate::entities::{
+    notebooks::{self},
+    saved_queries::{self},
+};
+use crate::names;
+use crate::query::{self, Filter, Term};
+use sea_orm::sea_query::{Expr, ExprTrait, Func};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+};
+
+// CRUD: Create
+// save `query` under `name`, replacing the query saved under it before
+pub async fn save_query<C: ConnectionTrait>(
+    db: &C,
+    name: String,
+    query: String,
+) -> Result<saved_queries::Model, DbErr> {
+    match get_saved_query(db, &name).await {
+        Ok(saved) => {
+            let mut saved: saved_queries::ActiveModel = saved.into();
+            saved.query = Set(query);
+            saved.update(db).await
+        }
+        Err(DbErr::RecordNotFound(_)) => {
+            let new_query =
+                saved_queries::ActiveModel { id: NotSet, query_name: Set(name), query: Set(query) };
+            new_query.insert(db).await
+        }
+        Err(err) => Err(err),
+    }
+}
+
+// CRUD: Read (Get)
+pub async fn get_all_saved_queries<C: ConnectionTrait>(
+    db: &C,
+) -> Result<Vec<saved_queries::Model>, DbErr> {
+    SavedQueries::find().order_by_asc(saved_queries::Column::QueryName).all(db).await
+}
+
+// lookup by name, ignoring case and spacing
+pub async fn get_saved_query<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+) -> Result<saved_queries::Model, DbErr> {
+    SavedQueries::find()
+        .filter(
+            Expr::expr(Func::lower(Expr::col(saved_queries::Column::QueryName)))
+                .eq(names::fold(name)),
+        )
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Query with name {} not found", name)))
+}
+
+// parse a stored filter, following an @name reference to a saved query, with
+// smart notebooks expanded as in `expand`
+pub async fn load_filter<C: ConnectionTrait>(db: &C, text: &str) -> Result<Filter, DbErr> {
+    load_filter_within(db, text, &[]).await
+}
+
+// `load_filter` for the filter of a smart notebook; `within` holds the folded
+// names of the smart notebooks being expanded, so a filter that leads back to
+// one of them is an error instead of an endless loop
+pub async fn load_filter_within<C: ConnectionTrait>(
+    db: &C,
+    text: &str,
+    within: &[String],
+) -> Result<Filter, DbErr> {
+    let text = match text.trim().strip_prefix('@') {
+        Some(name) => get_saved_query(db, name).await?.query,
+        None => text.to_string(),
+    };
+    let filter = query::parse(&text)
+        .map_err(|err| DbErr::Custom(format!("invalid filter '{}': {}", text, err)))?;
+    expand_within(db, &filter, within).await
+}
+
+// `filter` with every notebook: term that names a smart notebook replaced by
+// that notebook's filter, since smart notebooks have no NotebookNotes rows for
+// the compiled query to find
+pub async fn expand<C: ConnectionTrait>(db: &C, filter: &Filter) -> Result<Filter, DbErr> {
+    expand_within(db, filter, &[]).await
+}
+
+async fn expand_within<C: ConnectionTrait>(
+    db: &C,
+    filter: &Filter,
+    within: &[String],
+) -> Result<Filter, DbErr> {
+    Ok(match filter {
+        Filter::And(left, right) => Filter::And(
+            Box::new(Box::pin(expand_within(db, left, within)).await?),
+            Box::new(Box::pin(expand_within(db, right, within)).await?),
+        ),
+        Filter::Or(left, right) => Filter::Or(
+            Box::new(Box::pin(expand_within(db, left, within)).await?),
+            Box::new(Box::pin(expand_within(db, right, within)).await?),
+        ),
+        Filter::Not(inner) => {
+            Filter::Not(Box::new(Box::pin(expand_within(db, inner, within)).await?))
+        }
+        Filter::Term(Term::Notebook(name)) => {
+            if within.contains(&names::fold(name)) {
+                return Err(DbErr::Custom(format!(
+                    "Smart notebook {} refers back to itself through its filter",
+                    name
+                )));
+            }
+            let smart = Notebooks::find()
+                .filter(
+                    Expr::expr(Func::lower(Expr::col(notebooks::Column::NotebookName)))
+                        .eq(names::fold(name)),
+                )
+                .filter(notebooks::Column::Filter.is_not_null())
+                .one(db)
+                .await?;
+            let Some(smart) = smart else {
+                return Ok(filter.clone());
+            };
+            let within = [within, &[names::fold(&smart.notebook_name)]].concat();
+            let text = smart.filter.unwrap_or_default();
+            Box::pin(load_filter_within(db, &text, &within)).await?
+        }
+        Filter::Term(_) => filter.clone(),
+    })
+}
+
+// CRUD: Delete
+// a query that smart notebooks read their notes from stays until they are removed
+pub async fn delete_saved_query<C: ConnectionTrait>(db: &C, name: &str) -> Result<String, DbErr> {
+    let saved = get_saved_query(db, name).await?;
+    let users: Vec<String> = Notebooks::find()
+        .filter(notebooks::Column::Filter.is_not_null())
+        .all(db)
+        .await?
+        .into_iter()
+        .filter(|book| {
+            book.filter.as_deref().and_then(|f| f.trim().strip_prefix('@')).map(names::fold)
+                == Some(names::fold(&saved.query_name))
+        })
+        .map(|book| book.notebook_name)
+        .collect();
+    if !users.is_empty() {
+        return Err(DbErr::Custom(format!(
+            "Query {} is the filter of smart notebooks {}; remove them first",
+            saved.query_name,
+            users.join(", ")
+        )));
+    }
+    saved.delete(db).await?;
+    Ok(format!("Successfully deleted query with name: {}", name))
+}
@@ -0,0 +1,221 @@
+use crate::crud::{
+    note::{create_note, get_note_by_id, update_note},
+    notebook::add_note_to_notebook,
+    tag::add_tag_to_note,
+};
+use crate::entities::prelude::{NoteTags, NotebookNotes, Notes};
+use crate::entities::{note_tags, notebook_notes, notes};
+use crate::names;
+use pulldown_cmark::{Event, HeadingLevel, Parser, Tag, TagEnd};
+use sea_orm::sea_query::{Expr, ExprTrait, Func};
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter};
+use std::collections::HashMap;
+
+// a note body split at its headings: the text before the first heading and a
+// (heading, body) pair per section
+pub struct Sections {
+    pub preamble: String,
+    pub sections: Vec<(String, String)>,
+}
+
+// split markdown at headings of `level`, or at the top-most heading level used;
+// a heading that repeats `title` stays in the text rather than starting a section
+pub fn sections(content: &str, level: Option<u8>, title: &str) -> Sections {
+    let mut headings = Vec::new();
+    let mut current: Option<(HeadingLevel, usize, String)> = None;
+    for (event, range) in Parser::new(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                current = Some((level, range.start, String::new()))
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, title)) = current.as_mut() {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, start, title)) = current.take() {
+                    headings.push((level as u8, start, range.end, title));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    headings.retain(|h| names::fold(&h.3) != names::fold(title));
+    let level = level.or_else(|| headings.iter().map(|h| h.0).min());
+    let headings: Vec<_> = headings.into_iter().filter(|h| Some(h.0) == level).collect();
+    let Some(first) = headings.first() else {
+        return Sections { preamble: content.to_string(), sections: Vec::new() };
+    };
+
+    let preamble = content[..first.1].trim().to_string();
+    let sections = headings
+        .iter()
+        .enumerate()
+        .map(|(i, (_, _, body_start, title))| {
+            let end = headings.get(i + 1).map_or(content.len(), |next| next.1);
+            (title.trim().to_string(), content[*body_start..end].trim().to_string())
+        })
+        .collect();
+    Sections { preamble, sections }
+}
+
+// the notes `split_note` would create, as (title, body): each title is the
+// heading prefixed with the note's title, numbered when a heading repeats; fails
+// when a title is already taken, so it can be checked before any change is made
+pub async fn split_plan<C: ConnectionTrait>(
+    db: &C,
+    note: &notes::Model,
+    level: Option<u8>,
+) -> Result<Sections, DbErr> {
+    let Sections { preamble, sections } = sections(&note.content, level, &note.note_name);
+    if sections.is_empty() {
+        return Err(DbErr::Custom(format!("Note {} has no headings to split at", note.note_name)));
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let sections: Vec<(String, String)> = sections
+        .into_iter()
+        .map(|(heading, body)| {
+            let count = seen.entry(names::fold(&heading)).or_default();
+            *count += 1;
+            let title = match *count {
+                1 => format!("{}: {}", note.note_name, heading),
+                n => format!("{}: {} ({})", note.note_name, heading, n),
+            };
+            (names::normalize(&title), body)
+        })
+        .collect();
+
+    let folded: Vec<String> = sections.iter().map(|(title, _)| names::fold(title)).collect();
+    let taken: Vec<String> = Notes::find()
+        .filter(Expr::expr(Func::lower(Expr::col(notes::Column::NoteName))).is_in(folded))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|n| format!("'{}'", n.note_name))
+        .collect();
+    if !taken.is_empty() {
+        return Err(DbErr::Custom(format!(
+            "Cannot split note {}, these titles are taken: {}",
+            note.note_name,
+            taken.join(", ")
+        )));
+    }
+    Ok(Sections { preamble, sections })
+}
+
+// CRUD: Split
+// break a note into one note per heading; the new notes get the tags and notebooks
+// of the original, which keeps its preamble and a [[link]] to each section
+pub async fn split_note<C: ConnectionTrait>(
+    db: &C,
+    id: i64,
+    level: Option<u8>,
+) -> Result<Vec<notes::Model>, DbErr> {
+    let note = get_note_by_id(db, id).await?;
+    let Sections { preamble, sections } = split_plan(db, &note, level).await?;
+
+    let tag_ids: Vec<i64> = NoteTags::find()
+        .filter(note_tags::Column::NoteId.eq(id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|t| t.tag_id)
+        .collect();
+    let notebook_ids: Vec<i64> = NotebookNotes::find()
+        .filter(notebook_notes::Column::NoteId.eq(id))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|b| b.notebook_id)
+        .collect();
+
+    let mut created = Vec::new();
+    let mut links = Vec::new();
+    for (title, body) in sections {
+        let new_note = create_note(db, title, body).await?;
+        for tag_id in &tag_ids {
+            add_tag_to_note(db, new_note.id, *tag_id).await?;
+        }
+        for notebook_id in &notebook_ids {
+            add_note_to_notebook(db, *notebook_id, new_note.id).await?;
+        }
+        links.push(format!("- [[{}]]", new_note.note_name));
+        created.push(new_note);
+    }
+
+    let content = match preamble.as_str() {
+        "" => links.join("\n"),
+        preamble => format!("{}\n\n{}", preamble, links.join("\n")),
+    };
+    update_note(db, id, note.note_name, content).await?;
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sections;
+
+    fn headings(content: &str, level: Option<u8>, title: &str) -> Vec<String> {
+        sections(content, level, title).sections.into_iter().map(|(heading, _)| heading).collect()
+    }
+
+    #[test]
+    fn splits_at_the_top_most_level_used() {
+        let content = "## Iter\nnext\n### Adapters\nmap\n## IntoIter\nowned\n";
+        let split = sections(content, None, "Vec");
+        assert_eq!(
+            split.sections,
+            vec![
+                (String::from("Iter"), String::from("next\n### Adapters\nmap")),
+                (String::from("IntoIter"), String::from("owned")),
+            ]
+        );
+        assert_eq!(split.preamble, "");
+    }
+
+    #[test]
+    fn splits_at_the_level_asked_for() {
+        let content = "## Iter\nnext\n### Adapters\nmap\n### Consumers\nsum\n";
+        assert_eq!(headings(content, Some(3), "Vec"), ["Adapters", "Consumers"]);
+        // text above the first heading of that level is kept as the preamble
+        assert_eq!(sections(content, Some(3), "Vec").preamble, "## Iter\nnext");
+    }
+
+    #[test]
+    fn keeps_text_before_the_first_heading() {
+        let split = sections("A growable array.\n\n# Example\nvec![1]\n", None, "Vec");
+        assert_eq!(split.preamble, "A growable array.");
+        assert_eq!(headings("intro\n# Example\nx\n", None, "Vec"), ["Example"]);
+    }
+
+    #[test]
+    fn a_heading_repeating_the_title_stays_in_the_preamble() {
+        let content = "# Vec\nintro\n## Example\nx\n## Notes\ny\n";
+        let split = sections(content, None, "Vec");
+        assert_eq!(split.preamble, "# Vec\nintro");
+        assert_eq!(headings(content, None, "vec"), ["Example", "Notes"]);
+    }
+
+    #[test]
+    fn hashes_in_fenced_code_are_not_headings() {
+        let content = "# Example\n```rust\n# fn main() {\n#[derive(Debug)]\n```\n# Notes\ny\n";
+        let split = sections(content, None, "Vec");
+        assert_eq!(headings(content, None, "Vec"), ["Example", "Notes"]);
+        assert_eq!(split.sections[0].1, "```rust\n# fn main() {\n#[derive(Debug)]\n```");
+    }
+
+    #[test]
+    fn heading_text_keeps_inline_code() {
+        assert_eq!(headings("# `Vec::push`\nx\n", None, "Vec"), ["Vec::push"]);
+    }
+
+    #[test]
+    fn no_headings_leaves_everything_in_the_preamble() {
+        let split = sections("just text\n", None, "Vec");
+        assert!(split.sections.is_empty());
+        assert_eq!(split.preamble, "just text\n");
+    }
+}
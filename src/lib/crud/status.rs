@@ -0,0 +1,111 @@
+use crate::crud::note::get_note_by_id;
+use crate::entities::prelude::NoteStatusHistory;
+use crate::entities::{note_status_history, notes};
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// how far a note has been studied, in order
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    #[default]
+    New,
+    Learning,
+    Understood,
+    Mastered,
+}
+
+impl Status {
+    pub const ALL: [Status; 4] =
+        [Status::New, Status::Learning, Status::Understood, Status::Mastered];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::New => "new",
+            Status::Learning => "learning",
+            Status::Understood => "understood",
+            Status::Mastered => "mastered",
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Status::ALL.into_iter().find(|status| status.as_str() == s).ok_or_else(|| {
+            format!("unknown status '{}', expected new, learning, understood or mastered", s)
+        })
+    }
+}
+
+// CRUD: Update
+// move a note to `status`, recording the change in its history; none when it
+// already has that status
+pub async fn set_status<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    status: Status,
+) -> Result<Option<note_status_history::Model>, DbErr> {
+    let note = get_note_by_id(db, note_id).await?;
+    if note.status == status.as_str() {
+        return Ok(None);
+    }
+    let from_status = note.status.clone();
+    let mut note: notes::ActiveModel = note.into();
+    note.status = Set(status.to_string());
+    note.update(db).await?;
+
+    let event = note_status_history::ActiveModel {
+        id: NotSet,
+        note_id: Set(note_id),
+        from_status: Set(from_status),
+        to_status: Set(status.to_string()),
+        changed_at: NotSet,
+    };
+    event.insert(db).await.map(Some)
+}
+
+// CRUD: Read (Get)
+// the status changes of a note, oldest first
+pub async fn get_status_history<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+) -> Result<Vec<note_status_history::Model>, DbErr> {
+    NoteStatusHistory::find()
+        .filter(note_status_history::Column::NoteId.eq(note_id))
+        .order_by_asc(note_status_history::Column::ChangedAt)
+        .all(db)
+        .await
+}
+
+// the status changes since `since`, oldest first, of every note or only of
+// `note_ids`
+pub async fn get_status_changes_since<C: ConnectionTrait>(
+    db: &C,
+    since: NaiveDateTime,
+    note_ids: Option<&[i64]>,
+) -> Result<Vec<note_status_history::Model>, DbErr> {
+    let mut select =
+        NoteStatusHistory::find().filter(note_status_history::Column::ChangedAt.gte(since));
+    if let Some(ids) = note_ids {
+        select = select.filter(note_status_history::Column::NoteId.is_in(ids.iter().copied()));
+    }
+    select.order_by_asc(note_status_history::Column::ChangedAt).all(db).await
+}
@@ -1 +1,151 @@
+use crate::crud::{paginate, ListOptions, Page};
+use crate::entities::prelude::{NoteTags, Notes, Tags};
+use crate::entities::{
+    note_tags::{self},
+    notes::{self},
+    tags::{self},
+};
+use crate::names;
+use sea_orm::sea_query::{Expr, ExprTrait, Func};
+use sea_orm::{
+    ActiveModelTrait,
+    ActiveValue::{NotSet, Set},
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, ModelTrait, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait,
+};
 
+// CRUD: Create
+pub async fn create_tag<C: ConnectionTrait>(db: &C, name: String) -> Result<tags::Model, DbErr> {
+    let new_tag = tags::ActiveModel { id: NotSet, tag_name: Set(name) };
+
+    new_tag.insert(db).await
+}
+
+// find a tag by name, ignoring case like the unique index does, creating it
+// when it doesn't exist yet
+pub async fn get_or_create_tag<C: ConnectionTrait>(
+    db: &C,
+    name: String,
+) -> Result<tags::Model, DbErr> {
+    let existing = Tags::find()
+        .filter(Expr::expr(Func::lower(Expr::col(tags::Column::TagName))).eq(names::fold(&name)))
+        .one(db)
+        .await?;
+    match existing {
+        Some(tag) => Ok(tag),
+        None => create_tag(db, name).await,
+    }
+}
+
+// create relationship between note and tag
+pub async fn add_tag_to_note<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    tag_id: i64,
+) -> Result<note_tags::Model, DbErr> {
+    let link = note_tags::ActiveModel { id: NotSet, note_id: Set(note_id), tag_id: Set(tag_id) };
+
+    link.insert(db).await
+}
+
+// CRUD: Read (Get)
+pub async fn get_all_tags<C: ConnectionTrait>(db: &C) -> Result<Vec<tags::Model>, DbErr> {
+    Tags::find().order_by_asc(tags::Column::TagName).all(db).await
+}
+
+// one page of tags, sorted and limited by `options`
+pub async fn list_tags<C: ConnectionTrait>(
+    db: &C,
+    options: &ListOptions,
+) -> Result<Page<tags::Model>, DbErr> {
+    let (id, name) = (tags::Column::Id, tags::Column::TagName);
+    paginate(db, Tags::find(), id, name, options).await
+}
+
+pub async fn get_tag_by_name<C: ConnectionTrait>(
+    db: &C,
+    name: String,
+) -> Result<tags::Model, DbErr> {
+    Tags::find()
+        .filter(tags::Column::TagName.eq(name.to_owned()))
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Tag with name {} not found", name)))
+}
+
+// case-insensitive fuzzy lookup by name, best match first
+pub async fn find_tags_by_name<C: ConnectionTrait>(
+    db: &C,
+    name: &str,
+) -> Result<Vec<tags::Model>, DbErr> {
+    let tags = Tags::find().all(db).await?;
+    super::fuzzy_matches("Tag", "name", name, tags, |t| &t.tag_name)
+}
+
+pub async fn get_tag_by_id<C: ConnectionTrait>(db: &C, id: i64) -> Result<tags::Model, DbErr> {
+    Tags::find_by_id(id)
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("Tag with id {} not found", id)))
+}
+
+pub async fn get_tags_for_note<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+) -> Result<Vec<tags::Model>, DbErr> {
+    Tags::find()
+        .join(sea_orm::JoinType::InnerJoin, tags::Relation::NoteTags.def())
+        .filter(note_tags::Column::NoteId.eq(note_id))
+        .order_by_asc(tags::Column::TagName)
+        .all(db)
+        .await
+}
+
+pub async fn get_notes_with_tag<C: ConnectionTrait>(
+    db: &C,
+    tag_id: i64,
+) -> Result<Vec<notes::Model>, DbErr> {
+    Notes::find()
+        .join(sea_orm::JoinType::InnerJoin, notes::Relation::NoteTags.def())
+        .filter(note_tags::Column::TagId.eq(tag_id))
+        .all(db)
+        .await
+}
+
+// CRUD: Update
+pub async fn rename_tag<C: ConnectionTrait>(
+    db: &C,
+    id: i64,
+    new_name: String,
+) -> Result<tags::Model, DbErr> {
+    let mut tag: tags::ActiveModel = get_tag_by_id(db, id).await?.into();
+    tag.tag_name = Set(new_name);
+
+    tag.update(db).await
+}
+
+// CRUD: Delete
+pub async fn delete_tag_by_name<C: ConnectionTrait>(db: &C, name: String) -> Result<String, DbErr> {
+    let tag = get_tag_by_name(db, name).await?;
+    tag.delete(db).await?;
+    Ok("Successfully deleted tag".to_string())
+}
+
+pub async fn delete_tag_by_id<C: ConnectionTrait>(db: &C, id: i64) -> Result<String, DbErr> {
+    let deleted = Tags::delete_by_id(id).exec(db).await?;
+    Ok(format!("Successfully deleted {} tags", deleted.rows_affected))
+}
+
+// remove relationship between note and tag
+pub async fn remove_tag_from_note<C: ConnectionTrait>(
+    db: &C,
+    note_id: i64,
+    tag_id: i64,
+) -> Result<u64, DbErr> {
+    let deleted = NoteTags::delete_many()
+        .filter(note_tags::Column::NoteId.eq(note_id))
+        .filter(note_tags::Column::TagId.eq(tag_id))
+        .exec(db)
+        .await?;
+    Ok(deleted.rows_affected)
+}
@@ -1,18 +1,24 @@
 // database operations
 use sea_orm::{ConnectOptions, DatabaseConnection, DbErr};
+use std::path::Path;
 use std::time::Duration;
 
-pub fn set_db_options() -> ConnectOptions {
-    let mut opt = ConnectOptions::new("sqlite://db.sqlite?mode=rwc");
+// options for the sqlite database at `path`, created when missing, with foreign
+// keys enforced on every pooled connection; statements are logged when
+// `log_sql` is set, and any slower than `slow` always are
+pub fn set_db_options(path: &Path, log_sql: bool, slow: Duration) -> ConnectOptions {
+    let mut opt = ConnectOptions::new(format!("sqlite://{}?mode=rwc", path.display()));
     opt.max_connections(100)
         .min_connections(5)
         .connect_timeout(Duration::from_secs(8))
         .acquire_timeout(Duration::from_secs(8))
         .idle_timeout(Duration::from_secs(8))
         .max_lifetime(Duration::from_secs(8))
-        .sqlx_logging(false)
-        .sqlx_logging_level(log::LevelFilter::Info)
-        .set_schema_search_path("default_schema");
+        .sqlx_logging(true)
+        .sqlx_logging_level(if log_sql { log::LevelFilter::Info } else { log::LevelFilter::Off })
+        .sqlx_slow_statements_logging_settings(log::LevelFilter::Warn, slow)
+        .set_schema_search_path("default_schema")
+        .map_sqlx_sqlite_opts(|opts| opts.foreign_keys(true));
     opt
 }
 
@@ -0,0 +1,225 @@
+// database health checks and repairs
+use crate::crud::{merge::*, note::get_all_notes, notebook::get_all_notebooks, tag::get_all_tags};
+use crate::entities::prelude::{Notebooks, Notes, Tags};
+use crate::entities::{notebooks, notes, tags};
+use crate::names;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, Statement};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Notebook,
+    Note,
+    Tag,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Notebook => write!(f, "notebook"),
+            Kind::Note => write!(f, "note"),
+            Kind::Tag => write!(f, "tag"),
+        }
+    }
+}
+
+// the outcome of every check `modnote doctor` runs
+#[derive(Serialize)]
+pub struct Report {
+    // whether sqlite enforces the foreign keys on this connection
+    pub foreign_keys: bool,
+    // the problems `PRAGMA integrity_check` found; these can't be repaired here
+    pub integrity: Vec<String>,
+    pub orphans: Vec<Orphan>,
+    pub empty_names: Vec<EmptyName>,
+    pub duplicates: Vec<DuplicateGroup>,
+}
+
+impl Report {
+    // the problems `repair` can fix
+    pub fn repairable(&self) -> usize {
+        let duplicates: usize = self.duplicates.iter().map(|g| g.duplicates.len()).sum();
+        self.orphans.len() + self.empty_names.len() + duplicates
+    }
+}
+
+// a row pointing at a record that no longer exists, left behind by a delete made
+// while foreign keys were off
+#[derive(Serialize)]
+pub struct Orphan {
+    pub table: String,
+    pub rowid: i64,
+    pub parent: String,
+}
+
+#[derive(Serialize)]
+pub struct EmptyName {
+    pub kind: Kind,
+    pub id: i64,
+}
+
+// records whose names are equal once normalized and case-folded; the one with
+// the lowest id is kept when merging
+#[derive(Serialize)]
+pub struct DuplicateGroup {
+    pub kind: Kind,
+    pub keep: (i64, String),
+    pub duplicates: Vec<(i64, String)>,
+}
+
+pub async fn check<C: ConnectionTrait>(db: &C) -> Result<Report, DbErr> {
+    Ok(Report {
+        foreign_keys: foreign_keys_enabled(db).await?,
+        integrity: integrity_check(db).await?,
+        orphans: find_orphans(db).await?,
+        empty_names: find_empty_names(db).await?,
+        duplicates: find_duplicates(db).await?,
+    })
+}
+
+// fix what `check` found: orphan rows are deleted, records with an empty name
+// are named after their id and duplicates merged; run it on a transaction
+pub async fn repair<C: ConnectionTrait>(db: &C, report: &Report) -> Result<usize, DbErr> {
+    for orphan in &report.orphans {
+        let sql = format!(r#"DELETE FROM "{}" WHERE rowid = ?"#, orphan.table.replace('"', ""));
+        db.execute_raw(Statement::from_sql_and_values(
+            db.get_database_backend(),
+            sql,
+            [orphan.rowid.into()],
+        ))
+        .await?;
+    }
+    for EmptyName { kind, id } in &report.empty_names {
+        let name = Expr::value(format!("untitled-{}", id));
+        match kind {
+            Kind::Notebook => Notebooks::update_many()
+                .col_expr(notebooks::Column::NotebookName, name)
+                .filter(notebooks::Column::Id.eq(*id))
+                .exec(db)
+                .await
+                .map(|_| ())?,
+            Kind::Note => Notes::update_many()
+                .col_expr(notes::Column::NoteName, name)
+                .filter(notes::Column::Id.eq(*id))
+                .exec(db)
+                .await
+                .map(|_| ())?,
+            Kind::Tag => Tags::update_many()
+                .col_expr(tags::Column::TagName, name)
+                .filter(tags::Column::Id.eq(*id))
+                .exec(db)
+                .await
+                .map(|_| ())?,
+        }
+    }
+    let merged = merge_duplicates(db, &report.duplicates).await?;
+    Ok(report.orphans.len() + report.empty_names.len() + merged)
+}
+
+// whether the connection enforces foreign keys, which the connect options
+// turn on for every connection in the pool
+pub async fn foreign_keys_enabled<C: ConnectionTrait>(db: &C) -> Result<bool, DbErr> {
+    let row = db
+        .query_one_raw(Statement::from_string(db.get_database_backend(), "PRAGMA foreign_keys"))
+        .await?;
+    Ok(row.map(|row| row.try_get_by_index::<i32>(0)).transpose()?.unwrap_or_default() == 1)
+}
+
+pub async fn integrity_check<C: ConnectionTrait>(db: &C) -> Result<Vec<String>, DbErr> {
+    let rows = db
+        .query_all_raw(Statement::from_string(db.get_database_backend(), "PRAGMA integrity_check"))
+        .await?;
+    let mut problems = Vec::new();
+    for row in rows {
+        let message: String = row.try_get_by_index(0)?;
+        if message != "ok" {
+            problems.push(message);
+        }
+    }
+    Ok(problems)
+}
+
+// rows of the join, attachment and history tables whose note, notebook or tag
+// is gone, as sqlite's foreign key check reports them
+pub async fn find_orphans<C: ConnectionTrait>(db: &C) -> Result<Vec<Orphan>, DbErr> {
+    let rows = db
+        .query_all_raw(Statement::from_string(
+            db.get_database_backend(),
+            "PRAGMA foreign_key_check",
+        ))
+        .await?;
+    let mut orphans = Vec::new();
+    for row in rows {
+        orphans.push(Orphan {
+            table: row.try_get_by_index(0)?,
+            rowid: row.try_get_by_index(1)?,
+            parent: row.try_get_by_index(2)?,
+        });
+    }
+    Ok(orphans)
+}
+
+// notebooks, notes and tags whose name is empty once normalized, saved before
+// names were checked or written around the models
+pub async fn find_empty_names<C: ConnectionTrait>(db: &C) -> Result<Vec<EmptyName>, DbErr> {
+    let notebooks =
+        get_all_notebooks(db).await?.into_iter().map(|b| (Kind::Notebook, b.id, b.notebook_name));
+    let notes = get_all_notes(db).await?.into_iter().map(|n| (Kind::Note, n.id, n.note_name));
+    let tags = get_all_tags(db).await?.into_iter().map(|t| (Kind::Tag, t.id, t.tag_name));
+    Ok(notebooks
+        .chain(notes)
+        .chain(tags)
+        .filter(|(_, _, name)| names::normalize(name).is_empty())
+        .map(|(kind, id, _)| EmptyName { kind, id })
+        .collect())
+}
+
+pub async fn find_duplicates<C: ConnectionTrait>(db: &C) -> Result<Vec<DuplicateGroup>, DbErr> {
+    let notebooks = get_all_notebooks(db).await?.into_iter().map(|b| (b.id, b.notebook_name));
+    let notes = get_all_notes(db).await?.into_iter().map(|n| (n.id, n.note_name));
+    let tags = get_all_tags(db).await?.into_iter().map(|t| (t.id, t.tag_name));
+
+    let mut groups = group(Kind::Notebook, notebooks);
+    groups.extend(group(Kind::Note, notes));
+    groups.extend(group(Kind::Tag, tags));
+    Ok(groups)
+}
+
+pub async fn merge_duplicates<C: ConnectionTrait>(
+    db: &C,
+    groups: &[DuplicateGroup],
+) -> Result<usize, DbErr> {
+    let mut merged = 0;
+    for group in groups {
+        for (id, _) in &group.duplicates {
+            match group.kind {
+                Kind::Notebook => merge_notebooks(db, group.keep.0, *id).await.map(|_| ())?,
+                Kind::Note => merge_notes(db, group.keep.0, *id).await.map(|_| ())?,
+                Kind::Tag => merge_tags(db, group.keep.0, *id).await.map(|_| ())?,
+            }
+            merged += 1;
+        }
+    }
+    Ok(merged)
+}
+
+fn group(kind: Kind, records: impl Iterator<Item = (i64, String)>) -> Vec<DuplicateGroup> {
+    let mut by_name: BTreeMap<String, Vec<(i64, String)>> = BTreeMap::new();
+    for (id, name) in records {
+        by_name.entry(names::fold(&name)).or_default().push((id, name));
+    }
+    // empty names are reported on their own rather than as duplicates
+    by_name
+        .into_iter()
+        .filter(|(name, records)| !name.is_empty() && records.len() > 1)
+        .map(|(_, mut records)| {
+            records.sort_by_key(|(id, _)| *id);
+            let keep = records.remove(0);
+            DuplicateGroup { kind, keep, duplicates: records }
+        })
+        .collect()
+}
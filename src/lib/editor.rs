@@ -0,0 +1,29 @@
+// editing of note content in the user's editor
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process::Command;
+
+const DEFAULT_EDITOR: &str = "vi";
+
+// open `text` in `editor`, else $VISUAL or $EDITOR, and return what was saved
+pub fn edit(text: &str, editor: Option<&str>) -> io::Result<String> {
+    let editor = editor
+        .map(str::to_string)
+        .or_else(|| env::var("VISUAL").ok())
+        .or_else(|| env::var("EDITOR").ok())
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+
+    let mut file = tempfile::Builder::new().suffix(".md").tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or(DEFAULT_EDITOR);
+    let status = Command::new(program).args(parts).arg(file.path()).status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("{} exited with {}", editor, status)));
+    }
+    fs::read_to_string(file.path())
+}
@@ -2,8 +2,11 @@
 
 pub mod prelude;
 
+pub mod note_attachments;
+pub mod note_status_history;
 pub mod note_tags;
 pub mod notebook_notes;
 pub mod notebooks;
 pub mod notes;
+pub mod saved_queries;
 pub mod tags;
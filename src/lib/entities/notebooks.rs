@@ -1,14 +1,19 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0
 
 use sea_orm::entity::prelude::*;
+use sea_orm::ActiveValue::Set;
+use serde::{Deserialize, Serialize};
 
-#[derive(Default, Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "Notebooks")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
     pub notebook_name: String,
     pub description: String,
+    // the filter of a smart notebook, whose notes are found with it rather than
+    // through `NotebookNotes`
+    pub filter: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -23,4 +28,17 @@ impl Related<super::notebook_notes::Entity> for Entity {
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // store the notebook name normalized, rejecting one that is left empty
+    async fn before_save<C>(mut self, _db: &C, _insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if self.notebook_name.is_set() {
+            let name = crate::names::validate("notebook name", self.notebook_name.as_ref())?;
+            self.notebook_name = Set(name);
+        }
+        Ok(self)
+    }
+}
@@ -1,24 +1,46 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0
 
 use sea_orm::entity::prelude::*;
+use sea_orm::ActiveValue::Set;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "Notes")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i64,
     pub note_name: String,
     pub content: String,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+    // new, learning, understood or mastered
+    pub status: String,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::note_attachments::Entity")]
+    NoteAttachments,
+    #[sea_orm(has_many = "super::note_status_history::Entity")]
+    NoteStatusHistory,
     #[sea_orm(has_many = "super::note_tags::Entity")]
     NoteTags,
     #[sea_orm(has_many = "super::notebook_notes::Entity")]
     NotebookNotes,
 }
 
+impl Related<super::note_attachments::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NoteAttachments.def()
+    }
+}
+
+impl Related<super::note_status_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::NoteStatusHistory.def()
+    }
+}
+
 impl Related<super::note_tags::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::NoteTags.def()
@@ -31,4 +53,32 @@ impl Related<super::notebook_notes::Entity> for Entity {
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // store the note title normalized, rejecting one that is left empty, check
+    // the status and stamp the creation and update times
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if self.note_name.is_set() {
+            let name = crate::names::validate("note title", self.note_name.as_ref())?;
+            self.note_name = Set(name);
+        }
+        if insert && self.status.is_not_set() {
+            self.status = Set(String::from("new"));
+        }
+        if self.status.is_set() {
+            let status = self.status.as_ref().parse::<crate::crud::status::Status>();
+            status.map_err(DbErr::Custom)?;
+        }
+        let now = chrono::Utc::now().naive_utc();
+        if insert && self.created_at.is_not_set() {
+            self.created_at = Set(now);
+        }
+        if insert || self.is_changed() {
+            self.updated_at = Set(now);
+        }
+        Ok(self)
+    }
+}
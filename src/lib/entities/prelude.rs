@@ -1,7 +1,10 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0
 
+pub use super::note_attachments::Entity as NoteAttachments;
+pub use super::note_status_history::Entity as NoteStatusHistory;
 pub use super::note_tags::Entity as NoteTags;
 pub use super::notebook_notes::Entity as NotebookNotes;
 pub use super::notebooks::Entity as Notebooks;
 pub use super::notes::Entity as Notes;
+pub use super::saved_queries::Entity as SavedQueries;
 pub use super::tags::Entity as Tags;
@@ -1,8 +1,10 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 2.0
 
 use sea_orm::entity::prelude::*;
+use sea_orm::ActiveValue::Set;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "Tags")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -22,4 +24,17 @@ impl Related<super::note_tags::Entity> for Entity {
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
+#[async_trait::async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    // store the tag name normalized, rejecting one that is left empty
+    async fn before_save<C>(mut self, _db: &C, _insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if self.tag_name.is_set() {
+            let name = crate::names::validate("tag name", self.tag_name.as_ref())?;
+            self.tag_name = Set(name);
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,74 @@
+// case-insensitive fuzzy matching of titles and names
+
+// how well `candidate` matches `query`, lower is better, `None` when it doesn't match
+pub fn score(query: &str, candidate: &str) -> Option<usize> {
+    let query = query.trim().to_lowercase();
+    let candidate = candidate.trim().to_lowercase();
+    if query.is_empty() {
+        return None;
+    }
+    if candidate == query {
+        return Some(0);
+    }
+    if candidate.starts_with(&query) {
+        return Some(1);
+    }
+    if candidate.contains(&query) {
+        return Some(2);
+    }
+    if is_subsequence(&query, &candidate) {
+        return Some(3);
+    }
+    // allow a typo or two, scaled with the length of the query
+    let distance = levenshtein(&query, &candidate);
+    (distance <= (query.chars().count() / 4).max(1)).then_some(3 + distance)
+}
+
+// indexes of the candidates matching `query`, best match first; an exact
+// case-insensitive match hides every other candidate
+pub fn rank<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<usize> {
+    let mut scored: Vec<(usize, usize)> = candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, c)| score(query, c).map(|s| (i, s)))
+        .collect();
+    scored.sort_by_key(|&(i, s)| (s, i));
+    if scored.first().is_some_and(|&(_, s)| s == 0) {
+        scored.retain(|&(_, s)| s == 0);
+    }
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+// the `n` candidates closest to `query` by edit distance, for "did you mean" hints
+pub fn closest<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    n: usize,
+) -> Vec<String> {
+    let query = query.to_lowercase();
+    let mut scored: Vec<(usize, &str)> =
+        candidates.into_iter().map(|c| (levenshtein(&query, &c.to_lowercase()), c)).collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored.into_iter().take(n).map(|(_, c)| c.to_string()).collect()
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle.chars().all(|c| haystack.any(|h| h == c))
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + usize::from(ca != *cb);
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
@@ -0,0 +1,47 @@
+// note content and attachments read from a file or stdin; input over the size
+// limit is refused after reading at most one byte past it, so a runaway pipe
+// can't fill memory
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+// the text of `path`, or of stdin when it is `-`
+pub fn read_content(path: &Path, limit: u64) -> Result<String> {
+    let bytes = read(path, limit, "max_content_bytes")?;
+    String::from_utf8(bytes).with_context(|| format!("{} is not valid utf-8", name(path)))
+}
+
+// the bytes of a file to attach, or of stdin when it is `-`
+pub fn read_attachment(path: &Path, limit: u64) -> Result<Vec<u8>> {
+    read(path, limit, "max_attachment_bytes")
+}
+
+fn read(path: &Path, limit: u64, setting: &str) -> Result<Vec<u8>> {
+    let reader: Box<dyn Read> = if path == Path::new("-") {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(path).with_context(|| format!("can't read {}", path.display()))?)
+    };
+    let mut bytes = Vec::new();
+    reader
+        .take(limit.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("can't read {}", name(path)))?;
+    if bytes.len() as u64 > limit {
+        bail!(
+            "{} is larger than the limit of {} bytes, raise it with `modnote config set {} <bytes>`",
+            name(path),
+            limit,
+            setting
+        );
+    }
+    Ok(bytes)
+}
+
+fn name(path: &Path) -> String {
+    match path == Path::new("-") {
+        true => String::from("stdin"),
+        false => path.display().to_string(),
+    }
+}
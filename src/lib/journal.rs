@@ -0,0 +1,230 @@
+// daily study journal: one note per day in the Journal notebook, with an
+// activity section listing the notes created or updated that day; days are
+// local, like the dates of the filter language and of note templates
+use crate::crud::{note::*, notebook::*, query::expand};
+use crate::entities::prelude::{Notebooks, Notes};
+use crate::entities::{notebooks, notes};
+use crate::names;
+use crate::query::{self, Cmp, DateField, Day, Filter, Term};
+use chrono::{Datelike, Days, NaiveDate};
+use sea_orm::sea_query::{Expr, ExprTrait, Func};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ConnectionTrait, DbErr, EntityTrait, QueryFilter,
+};
+use serde::Serialize;
+
+pub const NOTEBOOK: &str = "Journal";
+const HEADING: &str = "## Activity";
+
+// the notes created or updated on a day, journal entries left out
+#[derive(Debug, Serialize)]
+pub struct Activity {
+    pub date: NaiveDate,
+    // title of the day's journal note, if one was written
+    pub entry: Option<String>,
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+}
+
+pub fn title(date: NaiveDate) -> String {
+    format!("Journal {}", date)
+}
+
+pub async fn activity<C: ConnectionTrait>(db: &C, date: NaiveDate) -> Result<Activity, DbErr> {
+    let on = |field| term(Term::Date(field, Cmp::Eq, Day::On(date)));
+    let not_journal = Filter::Not(Box::new(term(Term::Notebook(NOTEBOOK.to_string()))));
+    let created = and(on(DateField::Created), not_journal.clone());
+    let updated = and(
+        and(on(DateField::Updated), Filter::Not(Box::new(on(DateField::Created)))),
+        not_journal,
+    );
+    let entry = match get_note_by_title(db, title(date)).await {
+        Ok(note) => Some(note.note_name),
+        Err(DbErr::RecordNotFound(_)) => None,
+        Err(err) => return Err(err),
+    };
+    Ok(Activity {
+        date,
+        entry,
+        created: titles(db, &created).await?,
+        updated: titles(db, &updated).await?,
+    })
+}
+
+// the activity of each day of the week holding `date`, from monday up to `date`
+pub async fn week<C: ConnectionTrait>(db: &C, date: NaiveDate) -> Result<Vec<Activity>, DbErr> {
+    let monday = date - Days::new(date.weekday().num_days_from_monday() as u64);
+    let mut days = Vec::new();
+    for day in monday.iter_days().take_while(|day| *day <= date) {
+        days.push(activity(db, day).await?);
+    }
+    Ok(days)
+}
+
+// the journal note of `date`, created in the Journal notebook when missing, with
+// its activity section brought up to date; run it on a transaction
+pub async fn today<C: ConnectionTrait>(db: &C, date: NaiveDate) -> Result<notes::Model, DbErr> {
+    let activity = activity(db, date).await?;
+    let note = match get_note_by_title(db, title(date)).await {
+        Ok(note) => note,
+        Err(DbErr::RecordNotFound(_)) => {
+            let notebook = journal_notebook(db).await?;
+            // the activity is rewritten on every refresh, so leave a place below
+            // it for the day's own notes
+            let note = create_note(db, title(date), String::from("## Notes\n\n")).await?;
+            add_note_to_notebook(db, notebook.id, note.id).await?;
+            note
+        }
+        Err(err) => return Err(err),
+    };
+    let content = with_section(&note.content, &section(&activity));
+    if content == note.content {
+        return Ok(note);
+    }
+    let mut note: notes::ActiveModel = note.into();
+    note.content = Set(content);
+    note.update(db).await
+}
+
+// the activity as a markdown section, linking each note with [[Title]]
+pub fn section(activity: &Activity) -> String {
+    let mut out = format!("{}\n\n", HEADING);
+    if activity.created.is_empty() && activity.updated.is_empty() {
+        out.push_str("Nothing studied yet.\n");
+    }
+    for title in &activity.created {
+        out.push_str(&format!("- [[{}]] (new)\n", title));
+    }
+    for title in &activity.updated {
+        out.push_str(&format!("- [[{}]] (updated)\n", title));
+    }
+    out
+}
+
+// `content` with its activity section replaced by `section`, or with `section`
+// put first when it has none; text outside the section is left alone
+fn with_section(content: &str, section: &str) -> String {
+    let Some(start) = content.lines().position(|line| line.trim_end() == HEADING) else {
+        return match content.trim() {
+            "" => section.to_string(),
+            text => format!("{}\n{}\n", section, text),
+        };
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| line.starts_with("## "))
+        .map_or(lines.len(), |i| start + 1 + i);
+    let mut out = lines[..start].join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(section);
+    if end < lines.len() {
+        out.push('\n');
+        out.push_str(&lines[end..].join("\n"));
+        out.push('\n');
+    }
+    out
+}
+
+// the Journal notebook, ignoring case, created when missing
+async fn journal_notebook<C: ConnectionTrait>(db: &C) -> Result<notebooks::Model, DbErr> {
+    let existing = Notebooks::find()
+        .filter(
+            Expr::expr(Func::lower(Expr::col(notebooks::Column::NotebookName)))
+                .eq(names::fold(NOTEBOOK)),
+        )
+        .one(db)
+        .await?;
+    match existing {
+        Some(notebook) => Ok(notebook),
+        None => {
+            create_notebook(db, NOTEBOOK.to_string(), String::from("Daily study journal")).await
+        }
+    }
+}
+
+async fn titles<C: ConnectionTrait>(db: &C, filter: &Filter) -> Result<Vec<String>, DbErr> {
+    let filter = expand(db, filter).await?;
+    let mut notes = Notes::find().filter(query::compile(&filter)).all(db).await?;
+    notes.sort_by_key(|note| note.note_name.to_lowercase());
+    Ok(notes.into_iter().map(|note| note.note_name).collect())
+}
+
+fn term(term: Term) -> Filter {
+    Filter::Term(term)
+}
+
+fn and(left: Filter, right: Filter) -> Filter {
+    Filter::And(Box::new(left), Box::new(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity(created: &[&str], updated: &[&str]) -> Activity {
+        Activity {
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            entry: None,
+            created: created.iter().map(|t| t.to_string()).collect(),
+            updated: updated.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn section_links_each_note() {
+        assert_eq!(
+            section(&activity(&["Vec"], &["Option", "Result"])),
+            "## Activity\n\n- [[Vec]] (new)\n- [[Option]] (updated)\n- [[Result]] (updated)\n"
+        );
+        assert_eq!(section(&activity(&[], &[])), "## Activity\n\nNothing studied yet.\n");
+    }
+
+    #[test]
+    fn blank_content_becomes_the_section() {
+        let new = section(&activity(&["Vec"], &[]));
+        assert_eq!(with_section("", &new), new);
+        assert_eq!(with_section("  \n\n", &new), new);
+    }
+
+    #[test]
+    fn section_goes_first_when_missing() {
+        let new = section(&activity(&["Vec"], &[]));
+        assert_eq!(
+            with_section("\nfelt good today\n", &new),
+            format!("{}\nfelt good today\n", new)
+        );
+    }
+
+    #[test]
+    fn section_at_the_end_is_replaced() {
+        let new = section(&activity(&["Vec"], &[]));
+        assert_eq!(
+            with_section("# Notes\nintro\n## Activity\n\n- [[Old]] (new)\n", &new),
+            format!("# Notes\nintro\n{}", new)
+        );
+    }
+
+    #[test]
+    fn text_after_the_section_is_kept() {
+        let new = section(&activity(&[], &["Vec"]));
+        let content = "## Activity\n\nNothing studied yet.\n\n## Thoughts\nslices next\n";
+        assert_eq!(with_section(content, &new), format!("{}\n## Thoughts\nslices next\n", new));
+    }
+
+    #[test]
+    fn subheadings_stay_inside_the_section() {
+        let new = section(&activity(&["Vec"], &[]));
+        let content = "## Activity\n### old\n- [[Old]] (new)\n## Thoughts\n";
+        assert_eq!(with_section(content, &new), format!("{}\n## Thoughts\n", new));
+    }
+
+    #[test]
+    fn rewriting_twice_changes_nothing() {
+        let new = section(&activity(&["Vec"], &["Option"]));
+        let once = with_section("intro\n## Activity\nold\n## Thoughts\nmore\n", &new);
+        assert_eq!(with_section(&once, &new), once);
+    }
+}
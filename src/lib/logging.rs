@@ -0,0 +1,67 @@
+// log output: human-readable on stderr at a level picked with -v/-q or $RUST_LOG,
+// and optionally as json lines in a file
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+pub struct LogOptions<'a> {
+    // -v count minus -q count; 0 shows warnings and errors
+    pub verbosity: i8,
+    // log every sql statement with its run time
+    pub sql: bool,
+    // also write json lines here
+    pub file: Option<&'a Path>,
+    // leave the terminal alone, as the tui owns it
+    pub quiet_terminal: bool,
+}
+
+pub fn init(options: &LogOptions) -> Result<()> {
+    // the terminal follows -v/-q, while a log file gets at least info
+    let terminal = match options.quiet_terminal {
+        true => None,
+        false => {
+            let sql = options.sql && options.verbosity >= 0;
+            let filter = filter(options.verbosity, sql)?;
+            Some(fmt::layer().with_writer(std::io::stderr).with_filter(filter))
+        }
+    };
+    let file = match options.file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("can't open log file {}", path.display()))?;
+            let filter = filter(options.verbosity.max(1), options.sql)?;
+            Some(fmt::layer().json().with_writer(Mutex::new(file)).with_filter(filter))
+        }
+        None => None,
+    };
+    tracing_subscriber::registry().with(terminal).with(file).try_init()?;
+    Ok(())
+}
+
+// $RUST_LOG wins over -v/-q
+fn filter(verbosity: i8, sql: bool) -> Result<EnvFilter> {
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(directives) if !directives.trim().is_empty() => EnvFilter::new(directives),
+        _ => EnvFilter::new(level(verbosity)),
+    };
+    match sql {
+        true => Ok(filter.add_directive("sqlx::query=info".parse()?)),
+        false => Ok(filter),
+    }
+}
+
+fn level(verbosity: i8) -> &'static str {
+    match verbosity {
+        i8::MIN..=-2 => "off",
+        -1 => "error",
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    }
+}
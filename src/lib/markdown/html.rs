@@ -0,0 +1,85 @@
+// markdown to html conversion with build-time syntax highlighting
+use super::fence_language;
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use syntect::{highlighting::ThemeSet, html::highlighted_html_for_string, parsing::SyntaxSet};
+
+pub struct HtmlRenderer {
+    syntaxes: SyntaxSet,
+    themes: ThemeSet,
+}
+
+impl HtmlRenderer {
+    pub fn new() -> Self {
+        HtmlRenderer {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            themes: ThemeSet::load_defaults(),
+        }
+    }
+
+    // render markdown to html, highlighting fenced code blocks with a known language
+    pub fn render(&self, markdown: &str) -> String {
+        let mut events = Vec::new();
+        let mut code: Option<(String, String)> = None;
+
+        for event in Parser::new_ext(markdown, Options::all()) {
+            match event {
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let lang = match kind {
+                        CodeBlockKind::Fenced(info) => fence_language(&info).to_string(),
+                        CodeBlockKind::Indented => String::new(),
+                    };
+                    code = Some((lang, String::new()));
+                }
+                Event::Text(text) if code.is_some() => {
+                    if let Some((_, body)) = code.as_mut() {
+                        body.push_str(&text);
+                    }
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    if let Some((lang, body)) = code.take() {
+                        events.push(Event::Html(CowStr::from(self.highlight(&lang, &body))));
+                    }
+                }
+                event => events.push(event),
+            }
+        }
+
+        let mut out = String::new();
+        html::push_html(&mut out, events.into_iter());
+        out
+    }
+
+    fn highlight(&self, lang: &str, code: &str) -> String {
+        let syntax = match lang {
+            "" => None,
+            lang => self.syntaxes.find_syntax_by_token(lang),
+        };
+        let theme = &self.themes.themes["InspiredGitHub"];
+        match syntax.and_then(|s| highlighted_html_for_string(code, &self.syntaxes, s, theme).ok())
+        {
+            Some(highlighted) => highlighted,
+            None => format!("<pre><code>{}</code></pre>\n", escape(code)),
+        }
+    }
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
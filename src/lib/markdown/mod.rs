@@ -0,0 +1,16 @@
+// markdown rendering of note content for html pages and the terminal
+pub mod html;
+pub mod terminal;
+
+pub use html::HtmlRenderer;
+pub use terminal::TerminalRenderer;
+
+// the language of a fence info string such as "rust,no_run", defaulting bare
+// rustdoc attributes to rust the way doc comments do
+pub fn fence_language(info: &str) -> &str {
+    let lang = info.split([',', ' ']).next().unwrap_or_default().trim();
+    match lang {
+        "ignore" | "no_run" | "should_panic" | "compile_fail" | "edition2021" => "rust",
+        lang => lang,
+    }
+}
@@ -0,0 +1,230 @@
+// markdown to ansi-styled text for display in the terminal
+use super::fence_language;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use syntect::{
+    easy::HighlightLines, highlighting::ThemeSet, parsing::SyntaxSet,
+    util::as_24_bit_terminal_escaped,
+};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const ITALIC: &str = "\x1b[3m";
+const UNDERLINE: &str = "\x1b[4m";
+const STRIKE: &str = "\x1b[9m";
+const YELLOW: &str = "\x1b[33m";
+const MAGENTA: &str = "\x1b[35m";
+const CYAN: &str = "\x1b[36m";
+
+pub struct TerminalRenderer {
+    syntaxes: SyntaxSet,
+    themes: ThemeSet,
+}
+
+// state threaded through a single render
+#[derive(Default)]
+struct Writer {
+    out: String,
+    styles: Vec<&'static str>,
+    // one entry per open list, holding the next number for ordered lists
+    lists: Vec<Option<u64>>,
+    quote_depth: usize,
+    // whether nothing but styles has been written since the last newline
+    line_start: bool,
+    link: Option<String>,
+    code: Option<(String, String)>,
+}
+
+impl TerminalRenderer {
+    pub fn new() -> Self {
+        TerminalRenderer {
+            syntaxes: SyntaxSet::load_defaults_newlines(),
+            themes: ThemeSet::load_defaults(),
+        }
+    }
+
+    pub fn render(&self, markdown: &str) -> String {
+        let mut w = Writer { line_start: true, ..Writer::default() };
+
+        for event in Parser::new_ext(markdown, Options::all()) {
+            match event {
+                Event::Start(tag) => self.start(&mut w, tag),
+                Event::End(tag) => self.end(&mut w, tag),
+                Event::Text(text) => match w.code.as_mut() {
+                    Some((_, body)) => body.push_str(&text),
+                    None => w.text(&text),
+                },
+                Event::Code(code) => {
+                    w.push_style(YELLOW);
+                    w.text(&code);
+                    w.pop_style();
+                }
+                Event::SoftBreak => w.text(" "),
+                Event::HardBreak => w.newline(),
+                Event::Rule => {
+                    w.text(&"─".repeat(40));
+                    w.newline();
+                    w.newline();
+                }
+                Event::TaskListMarker(done) => w.text(if done { "[x] " } else { "[ ] " }),
+                Event::Html(html) | Event::InlineHtml(html) => w.text(&html),
+                _ => {}
+            }
+        }
+        w.out.trim_end().to_string() + "\n"
+    }
+
+    fn start(&self, w: &mut Writer, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                let style = match level {
+                    HeadingLevel::H1 => MAGENTA,
+                    _ => CYAN,
+                };
+                w.push_style(BOLD);
+                w.push_style(style);
+                if level == HeadingLevel::H1 {
+                    w.push_style(UNDERLINE);
+                }
+            }
+            Tag::BlockQuote(_) => {
+                w.quote_depth += 1;
+                w.push_style(DIM);
+            }
+            Tag::CodeBlock(kind) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => fence_language(&info).to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                w.code = Some((lang, String::new()));
+            }
+            Tag::List(start) => {
+                if !w.lists.is_empty() {
+                    w.newline();
+                }
+                w.lists.push(start);
+            }
+            Tag::Item => {
+                let indent = "  ".repeat(w.lists.len().saturating_sub(1));
+                let marker = match w.lists.last_mut() {
+                    Some(Some(n)) => {
+                        *n += 1;
+                        format!("{}. ", *n - 1)
+                    }
+                    _ => String::from("• "),
+                };
+                w.text(&format!("{}{}", indent, marker));
+            }
+            Tag::Emphasis => w.push_style(ITALIC),
+            Tag::Strong => w.push_style(BOLD),
+            Tag::Strikethrough => w.push_style(STRIKE),
+            Tag::Link { dest_url, .. } => {
+                w.link = Some(dest_url.to_string());
+                w.push_style(UNDERLINE);
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&self, w: &mut Writer, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(level) => {
+                w.pop_style();
+                w.pop_style();
+                if level == HeadingLevel::H1 {
+                    w.pop_style();
+                }
+                w.newline();
+                w.newline();
+            }
+            TagEnd::Paragraph => {
+                w.newline();
+                if w.lists.is_empty() {
+                    w.newline();
+                }
+            }
+            TagEnd::BlockQuote(_) => {
+                w.quote_depth -= 1;
+                w.pop_style();
+            }
+            TagEnd::CodeBlock => {
+                if let Some((lang, body)) = w.code.take() {
+                    w.out.push_str(&self.highlight(&lang, &body));
+                    w.out.push_str(RESET);
+                    w.newline();
+                }
+            }
+            TagEnd::List(_) => {
+                w.lists.pop();
+                if w.lists.is_empty() {
+                    w.newline();
+                }
+            }
+            TagEnd::Item if !w.line_start => w.newline(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => w.pop_style(),
+            TagEnd::Link => {
+                w.pop_style();
+                if let Some(url) = w.link.take() {
+                    w.push_style(DIM);
+                    w.text(&format!(" ({})", url));
+                    w.pop_style();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn highlight(&self, lang: &str, code: &str) -> String {
+        let syntax = match lang {
+            "" => None,
+            lang => self.syntaxes.find_syntax_by_token(lang),
+        };
+        let Some(syntax) = syntax else {
+            return code.lines().map(|l| format!("    {}\n", l)).collect();
+        };
+        let mut highlighter = HighlightLines::new(syntax, &self.themes.themes["base16-ocean.dark"]);
+        let mut out = String::new();
+        for line in code.split_inclusive('\n') {
+            match highlighter.highlight_line(line, &self.syntaxes) {
+                Ok(ranges) => out.push_str(&as_24_bit_terminal_escaped(&ranges, false)),
+                Err(_) => out.push_str(line),
+            }
+        }
+        out
+    }
+}
+
+impl Default for TerminalRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Writer {
+    fn text(&mut self, text: &str) {
+        if self.quote_depth > 0 && self.line_start {
+            self.out.push_str(&"│ ".repeat(self.quote_depth));
+        }
+        self.out.push_str(text);
+        self.line_start = false;
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.line_start = true;
+    }
+
+    fn push_style(&mut self, style: &'static str) {
+        self.styles.push(style);
+        self.out.push_str(style);
+    }
+
+    // ansi has no per-attribute reset, so reset everything and reapply what is still open
+    fn pop_style(&mut self) {
+        self.styles.pop();
+        self.out.push_str(RESET);
+        for style in &self.styles {
+            self.out.push_str(style);
+        }
+    }
+}
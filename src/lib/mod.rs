@@ -1,3 +1,23 @@
+pub mod complete;
+pub mod config;
 pub mod crud;
 pub mod db;
+pub mod doctor;
+pub mod editor;
 pub mod entities;
+pub mod fuzzy;
+pub mod input;
+pub mod journal;
+pub mod logging;
+pub mod markdown;
+pub mod names;
+pub mod pager;
+pub mod progress;
+pub mod publish;
+pub mod query;
+pub mod resolve;
+pub mod server;
+pub mod snippets;
+pub mod stats;
+pub mod templates;
+pub mod tui;
@@ -0,0 +1,55 @@
+// normalization of notebook, note and tag names
+use sea_orm::{DbErr, SqlErr};
+use thiserror::Error;
+
+// a name that is left empty once normalized; `what` says which, e.g. "note title"
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{what} cannot be empty")]
+pub struct EmptyName {
+    pub what: &'static str,
+}
+
+impl From<EmptyName> for DbErr {
+    fn from(err: EmptyName) -> Self {
+        DbErr::Custom(err.to_string())
+    }
+}
+
+// a name another record already has, ignoring case, which the unique indexes reject
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("{what} '{name}' is already taken")]
+pub struct NameTaken {
+    pub what: &'static str,
+    pub name: String,
+}
+
+// `err` as a `NameTaken` when it is a unique index rejecting `name`, so the user
+// sees which name clashed rather than sqlite's constraint message
+pub fn taken(err: DbErr, what: &'static str, name: &str) -> anyhow::Error {
+    match err.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => {
+            NameTaken { what, name: normalize(name) }.into()
+        }
+        _ => err.into(),
+    }
+}
+
+// the stored form of a name: trimmed, with runs of whitespace collapsed
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// the form two names are compared in when looking for duplicates; only ascii
+// letters are folded, like sqlite's NOCASE collation and lower() that the unique
+// indexes and lookups use
+pub fn fold(name: &str) -> String {
+    normalize(name).to_ascii_lowercase()
+}
+
+// the stored form of a name, or an error when nothing is left of it
+pub fn validate(what: &'static str, name: &str) -> Result<String, EmptyName> {
+    match normalize(name) {
+        name if name.is_empty() => Err(EmptyName { what }),
+        name => Ok(name),
+    }
+}
@@ -0,0 +1,39 @@
+// paging of long output through the configured pager or $PAGER
+use ratatui::crossterm::terminal;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+const DEFAULT_PAGER: &str = "less -R";
+
+// print `text`, piping it through `pager`, else $PAGER, when it is taller than
+// the terminal
+pub fn page(text: &str, pager: Option<&str>) -> io::Result<()> {
+    let rows = terminal::size().map(|(_, rows)| rows as usize).unwrap_or(usize::MAX);
+    if !io::stdout().is_terminal() || text.lines().count() < rows {
+        print!("{}", text);
+        return io::stdout().flush();
+    }
+
+    let pager = pager.map(str::to_string).or_else(|| env::var("PAGER").ok());
+    let pager = pager.filter(|p| !p.trim().is_empty());
+    let pager = pager.as_deref().unwrap_or(DEFAULT_PAGER);
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().unwrap_or("less");
+
+    let child = Command::new(program).args(parts).stdin(Stdio::piped()).spawn();
+    let Ok(mut child) = child else {
+        // fall back to plain output when the pager can't be started
+        print!("{}", text);
+        return io::stdout().flush();
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        // the user quitting the pager early closes the pipe, which is not an error
+        match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
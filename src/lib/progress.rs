@@ -0,0 +1,180 @@
+// study progress: how many notes have reached each status, overall and per
+// notebook, and an activity calendar with the current streak built from the
+// status history; days are local, like those of the filter language
+use crate::crud::status::{get_status_changes_since, Status};
+use crate::crud::{note::get_all_notes, notebook::*};
+use crate::entities::notes;
+use crate::query::{day_start, local_date};
+use chrono::{Datelike, Days, NaiveDate};
+use sea_orm::{ConnectionTrait, DbErr};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// notes per status
+#[derive(Debug, Default, Serialize)]
+pub struct Counts {
+    pub total: usize,
+    pub new: usize,
+    pub learning: usize,
+    pub understood: usize,
+    pub mastered: usize,
+}
+
+impl Counts {
+    pub fn of(notes: &[notes::Model]) -> Counts {
+        let mut counts = Counts { total: notes.len(), ..Counts::default() };
+        for note in notes {
+            match note.status.parse().unwrap_or_default() {
+                Status::New => counts.new += 1,
+                Status::Learning => counts.learning += 1,
+                Status::Understood => counts.understood += 1,
+                Status::Mastered => counts.mastered += 1,
+            }
+        }
+        counts
+    }
+
+    // share of the notes understood or mastered, in percent
+    pub fn completion(&self) -> u32 {
+        percent(self.understood + self.mastered, self.total)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotebookProgress {
+    pub notebook: String,
+    pub counts: Counts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NoteProgress {
+    pub title: String,
+    pub status: String,
+}
+
+// status changes per day, from the monday `weeks` weeks back up to the end day
+#[derive(Debug, Serialize)]
+pub struct Calendar {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: BTreeMap<NaiveDate, usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub overall: Counts,
+    pub notebooks: Vec<NotebookProgress>,
+    // the notes of the notebook asked for
+    pub notes: Vec<NoteProgress>,
+    // days in a row with a status change, up to today or yesterday, counted
+    // within the calendar
+    pub streak: usize,
+    pub calendar: Calendar,
+}
+
+// progress of every note and each notebook, or of the notes of one notebook
+pub async fn report<C: ConnectionTrait>(
+    db: &C,
+    notebook_id: Option<i64>,
+    today: NaiveDate,
+    weeks: u64,
+) -> Result<Report, DbErr> {
+    let (notes, notebooks) = match notebook_id {
+        Some(id) => (get_notes_in_notebook(db, id).await?, Vec::new()),
+        None => {
+            let mut notebooks = Vec::new();
+            for book in get_all_notebooks(db).await? {
+                let notes = get_notes_in_notebook(db, book.id).await?;
+                notebooks.push(NotebookProgress {
+                    notebook: book.notebook_name,
+                    counts: Counts::of(&notes),
+                });
+            }
+            (get_all_notes(db).await?, notebooks)
+        }
+    };
+
+    let calendar = calendar(db, &notes, notebook_id.is_some(), today, weeks).await?;
+    let mut listed: Vec<NoteProgress> = match notebook_id {
+        Some(_) => notes
+            .iter()
+            .map(|n| NoteProgress { title: n.note_name.clone(), status: n.status.clone() })
+            .collect(),
+        None => Vec::new(),
+    };
+    listed.sort_by_key(|note| note.title.to_lowercase());
+    Ok(Report {
+        overall: Counts::of(&notes),
+        notebooks,
+        notes: listed,
+        streak: streak(&calendar.days, today),
+        calendar,
+    })
+}
+
+// the calendar as rows of weekdays and columns of weeks, shaded by the number of
+// status changes each day
+pub fn render(calendar: &Calendar) -> String {
+    let mut out = String::new();
+    for (row, label) in ["Mon", "", "Wed", "", "Fri", "", "Sun"].iter().enumerate() {
+        out.push_str(&format!("{:<4}", label));
+        let mut day = calendar.start + Days::new(row as u64);
+        while day <= calendar.end {
+            out.push(shade(calendar.days.get(&day).copied().unwrap_or_default()));
+            day = day + Days::new(7);
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("    less {} {} {} {} more\n", shade(0), shade(1), shade(2), shade(4)));
+    out
+}
+
+async fn calendar<C: ConnectionTrait>(
+    db: &C,
+    notes: &[notes::Model],
+    only_these: bool,
+    today: NaiveDate,
+    weeks: u64,
+) -> Result<Calendar, DbErr> {
+    let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+    let start = monday - Days::new(7 * weeks.saturating_sub(1));
+    let ids: Vec<i64> = notes.iter().map(|note| note.id).collect();
+    let since = day_start(start);
+    let changes = get_status_changes_since(db, since, only_these.then_some(&ids[..])).await?;
+
+    let mut days = BTreeMap::new();
+    for change in changes {
+        *days.entry(local_date(change.changed_at)).or_insert(0) += 1;
+    }
+    Ok(Calendar { start, end: today, days })
+}
+
+// a streak still counts when nothing has changed yet today
+fn streak(days: &BTreeMap<NaiveDate, usize>, today: NaiveDate) -> usize {
+    let mut day = match days.contains_key(&today) {
+        true => today,
+        false => today - Days::new(1),
+    };
+    let mut streak = 0;
+    while days.contains_key(&day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+    streak
+}
+
+fn shade(changes: usize) -> char {
+    match changes {
+        0 => '·',
+        1 => '░',
+        2..=3 => '▒',
+        _ => '▓',
+    }
+}
+
+fn percent(part: usize, total: usize) -> u32 {
+    match total {
+        0 => 0,
+        total => (part * 100 / total) as u32,
+    }
+}
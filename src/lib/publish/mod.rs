@@ -0,0 +1,380 @@
+// read-only static html site generated from every notebook, note and tag
+use crate::crud::{attachment::*, note::*, notebook::*, tag::*};
+use crate::entities::{notebooks, notes, tags};
+use crate::markdown::{html::escape, HtmlRenderer};
+use anyhow::Result;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use sea_orm::DbConn;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// what a url path segment must encode: everything but the unreserved characters
+const PATH_SEGMENT: &AsciiSet =
+    &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+pub struct PublishSummary {
+    pub notebooks: usize,
+    pub notes: usize,
+    pub tags: usize,
+    pub attachments: usize,
+}
+
+struct Site {
+    notebooks: Vec<notebooks::Model>,
+    notes: Vec<notes::Model>,
+    tags: Vec<tags::Model>,
+    // note id -> notebook ids / tag ids the note belongs to
+    note_notebooks: BTreeMap<i64, Vec<i64>>,
+    note_tags: BTreeMap<i64, Vec<i64>>,
+    // note id -> ids of notes linking to it with [[Title]]
+    backlinks: BTreeMap<i64, Vec<i64>>,
+    // note id -> files attached to the note
+    attachments: BTreeMap<i64, Vec<AttachmentInfo>>,
+}
+
+pub async fn publish(db: &DbConn, out: &Path) -> Result<PublishSummary> {
+    let site = Site::load(db).await?;
+    let renderer = HtmlRenderer::new();
+
+    for dir in ["notebooks", "notes", "tags"] {
+        fs::create_dir_all(out.join(dir))?;
+    }
+    fs::write(out.join("style.css"), STYLE)?;
+    fs::write(out.join("search-index.js"), site.search_index())?;
+    fs::write(out.join("index.html"), site.index_page())?;
+
+    for book in &site.notebooks {
+        fs::write(out.join(notebook_path(book)), site.notebook_page(book))?;
+    }
+    for note in &site.notes {
+        fs::write(out.join(note_path(note)), site.note_page(note, &renderer))?;
+    }
+    // the data is loaded one attachment at a time rather than all held at once
+    for attachment in site.attachments.values().flatten() {
+        let path = out.join("attachments").join(attachment.note_id.to_string());
+        fs::create_dir_all(&path)?;
+        fs::write(path.join(&attachment.file_name), get_attachment_data(db, attachment.id).await?)?;
+    }
+    for tag in &site.tags {
+        fs::write(out.join(tag_path(tag)), site.tag_page(tag))?;
+    }
+
+    Ok(PublishSummary {
+        notebooks: site.notebooks.len(),
+        notes: site.notes.len(),
+        tags: site.tags.len(),
+        attachments: site.attachments.values().map(Vec::len).sum(),
+    })
+}
+
+impl Site {
+    async fn load(db: &DbConn) -> Result<Self> {
+        let notebooks = get_all_notebooks(db).await?;
+        let mut notes = get_all_notes(db).await?;
+        notes.sort_by_key(|n| n.note_name.to_lowercase());
+        let tags = get_all_tags(db).await?;
+
+        let mut note_notebooks: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for book in &notebooks {
+            for note in get_notes_in_notebook(db, book.id).await? {
+                note_notebooks.entry(note.id).or_default().push(book.id);
+            }
+        }
+        let mut note_tags: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for tag in &tags {
+            for note in get_notes_with_tag(db, tag.id).await? {
+                note_tags.entry(note.id).or_default().push(tag.id);
+            }
+        }
+
+        let mut attachments = BTreeMap::new();
+        for note in &notes {
+            let files = get_attachments_for_note(db, note.id).await?;
+            if !files.is_empty() {
+                attachments.insert(note.id, files);
+            }
+        }
+
+        let mut site = Site {
+            notebooks,
+            notes,
+            tags,
+            note_notebooks,
+            note_tags,
+            backlinks: BTreeMap::new(),
+            attachments,
+        };
+        let mut backlinks: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+        for note in &site.notes {
+            for title in wiki_links(&note.content) {
+                if let Some(target) = site.note_by_title(&title) {
+                    let sources = backlinks.entry(target.id).or_default();
+                    if target.id != note.id && !sources.contains(&note.id) {
+                        sources.push(note.id);
+                    }
+                }
+            }
+        }
+        site.backlinks = backlinks;
+        Ok(site)
+    }
+
+    fn note_by_title(&self, title: &str) -> Option<&notes::Model> {
+        self.notes.iter().find(|n| n.note_name.eq_ignore_ascii_case(title.trim()))
+    }
+
+    fn notes_where(&self, ids: impl Fn(i64) -> bool) -> Vec<&notes::Model> {
+        self.notes.iter().filter(|n| ids(n.id)).collect()
+    }
+
+    fn index_page(&self) -> String {
+        let mut body = String::from(
+            "<input id=\"search\" type=\"search\" placeholder=\"Search notes...\" autofocus>\n\
+             <ul id=\"results\"></ul>\n",
+        );
+        body.push_str("<h2>Notebooks</h2>\n<ul>\n");
+        for book in &self.notebooks {
+            let count = self.note_notebooks.values().filter(|ids| ids.contains(&book.id)).count();
+            body.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> <span class=\"meta\">{} notes</span></li>\n",
+                notebook_path(book),
+                escape(&book.notebook_name),
+                count
+            ));
+        }
+        body.push_str("</ul>\n<h2>Tags</h2>\n");
+        body.push_str(&self.tag_list(""));
+        body.push_str("<h2>All notes</h2>\n");
+        body.push_str(&note_list(&self.notes.iter().collect::<Vec<_>>(), ""));
+        body.push_str("<script src=\"search-index.js\"></script>\n<script>\n");
+        body.push_str(SEARCH_SCRIPT);
+        body.push_str("</script>\n");
+        layout("Modnote", "", &body)
+    }
+
+    fn notebook_page(&self, book: &notebooks::Model) -> String {
+        let notes = self.notes_where(|id| {
+            self.note_notebooks.get(&id).is_some_and(|books| books.contains(&book.id))
+        });
+        let body = format!(
+            "<p class=\"description\">{}</p>\n{}",
+            escape(&book.description),
+            note_list(&notes, "../")
+        );
+        layout(&book.notebook_name, "../", &body)
+    }
+
+    fn tag_page(&self, tag: &tags::Model) -> String {
+        let notes = self
+            .notes_where(|id| self.note_tags.get(&id).is_some_and(|tags| tags.contains(&tag.id)));
+        layout(&format!("#{}", tag.tag_name), "../", &note_list(&notes, "../"))
+    }
+
+    fn note_page(&self, note: &notes::Model, renderer: &HtmlRenderer) -> String {
+        let mut body = String::from("<p class=\"meta\">");
+        for book in self.notebooks.iter().filter(|b| {
+            self.note_notebooks.get(&note.id).is_some_and(|books| books.contains(&b.id))
+        }) {
+            body.push_str(&format!(
+                "<a href=\"../{}\">{}</a> ",
+                notebook_path(book),
+                escape(&book.notebook_name)
+            ));
+        }
+        for tag in self
+            .tags
+            .iter()
+            .filter(|t| self.note_tags.get(&note.id).is_some_and(|tags| tags.contains(&t.id)))
+        {
+            body.push_str(&format!(
+                "<a class=\"tag\" href=\"../{}\">#{}</a> ",
+                tag_path(tag),
+                escape(&tag.tag_name)
+            ));
+        }
+        body.push_str("</p>\n<article>\n");
+        body.push_str(&renderer.render(&self.resolve_wiki_links(&note.content)));
+        body.push_str("</article>\n");
+
+        if let Some(attachments) = self.attachments.get(&note.id) {
+            body.push_str("<h2>Attachments</h2>\n<ul>\n");
+            for attachment in attachments {
+                let href = escape(&format!("../{}", attachment_href(attachment)));
+                let name = escape(&attachment.file_name);
+                body.push_str(&format!(
+                    "<li><a href=\"{}\">{}</a> <span class=\"meta\">{} bytes</span>",
+                    href, name, attachment.size
+                ));
+                // images are shown inline as well
+                if attachment.mime_type.starts_with("image/") {
+                    body.push_str(&format!("<br><img src=\"{}\" alt=\"{}\">", href, name));
+                }
+                body.push_str("</li>\n");
+            }
+            body.push_str("</ul>\n");
+        }
+
+        if let Some(sources) = self.backlinks.get(&note.id) {
+            let sources = self.notes_where(|id| sources.contains(&id));
+            body.push_str("<h2>Backlinks</h2>\n");
+            body.push_str(&note_list(&sources, "../"));
+        }
+        layout(&note.note_name, "../", &body)
+    }
+
+    // replace [[Title]] with a markdown link to the note, leaving unknown titles as text
+    fn resolve_wiki_links(&self, content: &str) -> String {
+        let mut out = String::with_capacity(content.len());
+        let mut rest = content;
+        while let Some(start) = rest.find("[[") {
+            let Some(end) = rest[start..].find("]]") else { break };
+            let title = &rest[start + 2..start + end];
+            out.push_str(&rest[..start]);
+            match self.note_by_title(title) {
+                Some(target) => out.push_str(&format!("[{}](../{})", title, note_path(target))),
+                None => out.push_str(title),
+            }
+            rest = &rest[start + end + 2..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn tag_list(&self, root: &str) -> String {
+        let mut out = String::from("<p>");
+        for tag in &self.tags {
+            out.push_str(&format!(
+                "<a class=\"tag\" href=\"{}{}\">#{}</a> ",
+                root,
+                tag_path(tag),
+                escape(&tag.tag_name)
+            ));
+        }
+        out.push_str("</p>\n");
+        out
+    }
+
+    fn search_index(&self) -> String {
+        let entries: Vec<_> = self
+            .notes
+            .iter()
+            .map(|note| {
+                let tags: Vec<&str> = self
+                    .tags
+                    .iter()
+                    .filter(|t| {
+                        self.note_tags.get(&note.id).is_some_and(|tags| tags.contains(&t.id))
+                    })
+                    .map(|t| t.tag_name.as_str())
+                    .collect();
+                json!({
+                    "title": note.note_name,
+                    "url": note_path(note),
+                    "tags": tags,
+                    "text": note.content,
+                })
+            })
+            .collect();
+        format!("const SEARCH_INDEX = {};\n", serde_json::Value::Array(entries))
+    }
+}
+
+// titles referenced by [[Title]] wiki links in note content
+pub fn wiki_links(content: &str) -> Vec<String> {
+    let mut links = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find("[[") {
+        let Some(end) = rest[start..].find("]]") else { break };
+        links.push(rest[start + 2..start + end].trim().to_string());
+        rest = &rest[start + end + 2..];
+    }
+    links
+}
+
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+fn notebook_path(book: &notebooks::Model) -> String {
+    format!("notebooks/{}-{}.html", book.id, slugify(&book.notebook_name))
+}
+
+fn note_path(note: &notes::Model) -> String {
+    format!("notes/{}-{}.html", note.id, slugify(&note.note_name))
+}
+
+fn tag_path(tag: &tags::Model) -> String {
+    format!("tags/{}-{}.html", tag.id, slugify(&tag.tag_name))
+}
+
+// the file name is percent-encoded, so names with spaces, `#` or `?` still link
+fn attachment_href(attachment: &AttachmentInfo) -> String {
+    let name = utf8_percent_encode(&attachment.file_name, PATH_SEGMENT);
+    format!("attachments/{}/{}", attachment.note_id, name)
+}
+
+fn note_list(notes: &[&notes::Model], root: &str) -> String {
+    if notes.is_empty() {
+        return String::from("<p class=\"meta\">No notes</p>\n");
+    }
+    let mut out = String::from("<ul>\n");
+    for note in notes {
+        out.push_str(&format!(
+            "<li><a href=\"{}{}\">{}</a></li>\n",
+            root,
+            note_path(note),
+            escape(&note.note_name)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+fn layout(title: &str, root: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<link rel=\"stylesheet\" href=\"{root}style.css\">\n</head>\n\
+         <body>\n<nav><a href=\"{root}index.html\">modnote</a></nav>\n<h1>{title}</h1>\n{body}</body>\n</html>\n",
+        title = escape(title),
+        root = root,
+        body = body
+    )
+}
+
+const STYLE: &str = "body { max-width: 50rem; margin: 2rem auto; padding: 0 1rem; font-family: sans-serif; line-height: 1.5; }
+nav a { text-decoration: none; font-weight: bold; }
+pre { padding: 0.75rem; overflow-x: auto; background: #f6f8fa; }
+.meta { color: #666; }
+.tag { color: #0969da; margin-right: 0.25rem; }
+#search { width: 100%; padding: 0.5rem; font-size: 1rem; }
+";
+
+const SEARCH_SCRIPT: &str = "const input = document.getElementById('search');
+const results = document.getElementById('results');
+input.addEventListener('input', () => {
+  const terms = input.value.toLowerCase().split(/\\s+/).filter(t => t);
+  results.innerHTML = '';
+  if (!terms.length) return;
+  for (const note of SEARCH_INDEX) {
+    const haystack = [note.title, note.text, ...note.tags].join(' ').toLowerCase();
+    if (terms.every(t => haystack.includes(t))) {
+      const li = document.createElement('li');
+      const a = document.createElement('a');
+      a.href = note.url;
+      a.textContent = note.title;
+      li.appendChild(a);
+      results.appendChild(li);
+    }
+  }
+});
+";
@@ -0,0 +1,119 @@
+// compilation of a `Filter` into a condition on `Notes`; tag and notebook terms
+// become subqueries joining through the membership tables, so NOT excludes notes
+// that have the tag rather than rows that lack it
+use super::{day_start, Cmp, DateField, Filter, Term};
+use crate::entities::prelude::{NoteTags, NotebookNotes, Notebooks, Tags};
+use crate::entities::{note_tags, notebook_notes, notebooks, notes, tags};
+use crate::names;
+use chrono::Days;
+use sea_orm::sea_query::{Expr, ExprTrait, Func, LikeExpr, Query, SelectStatement};
+use sea_orm::{ColumnTrait, Condition};
+
+pub fn compile(filter: &Filter) -> Condition {
+    match filter {
+        Filter::And(left, right) => Condition::all().add(compile(left)).add(compile(right)),
+        Filter::Or(left, right) => Condition::any().add(compile(left)).add(compile(right)),
+        Filter::Not(inner) => compile(inner).not(),
+        Filter::Term(term) => term_condition(term),
+    }
+}
+
+fn term_condition(term: &Term) -> Condition {
+    match term {
+        Term::Tag(name) => Condition::all().add(notes::Column::Id.in_subquery(tagged_with(name))),
+        Term::Notebook(name) => Condition::all().add(notes::Column::Id.in_subquery(filed_in(name))),
+        Term::Title(text) => Condition::all().add(notes::Column::NoteName.like(containing(text))),
+        Term::Text(text) => Condition::any()
+            .add(notes::Column::NoteName.like(containing(text)))
+            .add(notes::Column::Content.like(containing(text))),
+        Term::Date(field, cmp, day) => {
+            let column = match field {
+                DateField::Created => notes::Column::CreatedAt,
+                DateField::Updated => notes::Column::UpdatedAt,
+            };
+            // dates cover the whole day, so compare against the start of the
+            // day and the start of the next one
+            let date = day.date();
+            let (start, next) = (day_start(date), day_start(date + Days::new(1)));
+            match cmp {
+                Cmp::Lt => Condition::all().add(column.lt(start)),
+                Cmp::Le => Condition::all().add(column.lt(next)),
+                Cmp::Eq => Condition::all().add(column.gte(start)).add(column.lt(next)),
+                Cmp::Ge => Condition::all().add(column.gte(start)),
+                Cmp::Gt => Condition::all().add(column.gte(next)),
+            }
+        }
+    }
+}
+
+// a LIKE pattern matching `text` anywhere, with `%` and `_` in it taken literally
+fn containing(text: &str) -> LikeExpr {
+    let mut pattern = String::from("%");
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    LikeExpr::new(pattern).escape('\\')
+}
+
+// ids of the notes tagged `name`, ignoring case
+fn tagged_with(name: &str) -> SelectStatement {
+    Query::select()
+        .column((NoteTags, note_tags::Column::NoteId))
+        .from(NoteTags)
+        .inner_join(
+            Tags,
+            Expr::col((Tags, tags::Column::Id)).equals((NoteTags, note_tags::Column::TagId)),
+        )
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((Tags, tags::Column::TagName)))).eq(names::fold(name)),
+        )
+        .to_owned()
+}
+
+// ids of the notes filed in the notebook `name`, ignoring case; a smart notebook
+// files nothing, so `crud::query::expand` swaps its filter in before compiling
+fn filed_in(name: &str) -> SelectStatement {
+    Query::select()
+        .column((NotebookNotes, notebook_notes::Column::NoteId))
+        .from(NotebookNotes)
+        .inner_join(
+            Notebooks,
+            Expr::col((Notebooks, notebooks::Column::Id))
+                .equals((NotebookNotes, notebook_notes::Column::NotebookId)),
+        )
+        .and_where(
+            Expr::expr(Func::lower(Expr::col((Notebooks, notebooks::Column::NotebookName))))
+                .eq(names::fold(name)),
+        )
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::prelude::Notes;
+    use crate::query::parse;
+    use sea_orm::{DbBackend, EntityTrait, QueryFilter, QueryTrait};
+
+    fn sql(query: &str) -> String {
+        let filter = parse(query).unwrap();
+        Notes::find().filter(compile(&filter)).build(DbBackend::Sqlite).to_string()
+    }
+
+    #[test]
+    fn like_wildcards_are_taken_literally() {
+        assert!(sql("title:50%").contains(r"LIKE '%50\%%' ESCAPE '\'"));
+        assert!(sql("text:a_b").contains(r"LIKE '%a\_b%' ESCAPE '\'"));
+        assert!(sql(r"title:a\b").contains(r"LIKE '%a\\b%' ESCAPE '\'"));
+    }
+
+    #[test]
+    fn not_excludes_notes_with_the_tag() {
+        let sql = sql("NOT tag:done");
+        assert!(sql.contains(r#"NOT "Notes"."id" IN (SELECT "NoteTags"."note_id""#), "{}", sql);
+    }
+}
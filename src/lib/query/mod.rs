@@ -0,0 +1,75 @@
+// the filter language of `modnote find`, e.g.
+// `tag:iterators AND notebook:std AND NOT tag:done AND updated>2026-01-01`;
+// dates may also count back from today, as in `updated<30d`
+pub mod compile;
+pub mod parse;
+
+pub use compile::compile;
+pub use parse::{parse, ParseError};
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Term(Term),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    // tagged with the tag of this name
+    Tag(String),
+    // filed in the notebook of this name
+    Notebook(String),
+    // title contains the text
+    Title(String),
+    // title or content contains the text
+    Text(String),
+    // created or updated before, on or after the day
+    Date(DateField, Cmp, Day),
+}
+
+// a calendar day, or one counted back from today when the filter is evaluated
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Day {
+    On(NaiveDate),
+    DaysAgo(u64),
+}
+
+impl Day {
+    pub fn date(self) -> NaiveDate {
+        match self {
+            Day::On(date) => date,
+            Day::DaysAgo(days) => Local::now().date_naive() - chrono::Days::new(days),
+        }
+    }
+}
+
+// days are local while the stored timestamps are utc: the utc time at which
+// the local `date` starts
+pub fn day_start(date: NaiveDate) -> NaiveDateTime {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    Local.from_local_datetime(&midnight).earliest().map_or(midnight, |start| start.naive_utc())
+}
+
+// the local day a stored utc timestamp falls on
+pub fn local_date(timestamp: NaiveDateTime) -> NaiveDate {
+    Local.from_utc_datetime(&timestamp).date_naive()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DateField {
+    Created,
+    Updated,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
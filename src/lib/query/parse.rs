@@ -0,0 +1,397 @@
+// parsing of filter text into a `Filter`; terms are joined by AND, OR and NOT
+// (any case), parentheses group, and juxtaposed terms are ANDed
+use super::{Cmp, DateField, Day, Filter, Term};
+use chrono::NaiveDate;
+use thiserror::Error;
+
+const FIELDS: &str = "tag, notebook, title, text, created or updated";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("column {column}: {message}")]
+pub struct ParseError {
+    // 1-based character column the problem was found at
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(column: usize, message: impl Into<String>) -> Self {
+        ParseError { column, message: message.into() }
+    }
+
+    // the query with a caret under the offending column
+    pub fn pointer(&self, query: &str) -> String {
+        format!("{}\n{}^", query, " ".repeat(self.column - 1))
+    }
+}
+
+pub fn parse(query: &str) -> Result<Filter, ParseError> {
+    let tokens = lex(query)?;
+    if tokens.is_empty() {
+        return Err(ParseError::new(1, "empty query"));
+    }
+    let mut parser = Parser { tokens, pos: 0, end: query.chars().count() + 1 };
+    let filter = parser.or()?;
+    match parser.tokens.get(parser.pos) {
+        Some(token) => Err(ParseError::new(token.column, "unexpected ')'")),
+        None => Ok(filter),
+    }
+}
+
+struct Token {
+    kind: Kind,
+    column: usize,
+}
+
+enum Kind {
+    Open,
+    Close,
+    // a word with its quotes removed; `bare` counts the characters before the
+    // first quote, which are the only ones that can hold a field or keyword
+    Atom { text: String, bare: usize },
+}
+
+fn lex(query: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let column = i + 1;
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token { kind: Kind::Open, column });
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token { kind: Kind::Close, column });
+                i += 1;
+            }
+            _ => {
+                let mut text = String::new();
+                let mut bare = None;
+                while i < chars.len() && !chars[i].is_whitespace() && !"()".contains(chars[i]) {
+                    if chars[i] != '"' {
+                        text.push(chars[i]);
+                        i += 1;
+                        continue;
+                    }
+                    bare.get_or_insert(text.chars().count());
+                    let open = i;
+                    i += 1;
+                    while i < chars.len() && chars[i] != '"' {
+                        text.push(chars[i]);
+                        i += 1;
+                    }
+                    if i == chars.len() {
+                        return Err(ParseError::new(open + 1, "unterminated quote"));
+                    }
+                    i += 1;
+                }
+                let bare = bare.unwrap_or(text.chars().count());
+                tokens.push(Token { kind: Kind::Atom { text, bare }, column });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    // column just past the end of the query
+    end: usize,
+}
+
+impl Parser {
+    fn or(&mut self) -> Result<Filter, ParseError> {
+        let mut left = self.and()?;
+        while self.keyword("OR") {
+            left = Filter::Or(Box::new(left), Box::new(self.and()?));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Filter, ParseError> {
+        let mut left = self.unary()?;
+        loop {
+            let explicit = self.keyword("AND");
+            if !explicit && !self.starts_filter() {
+                return Ok(left);
+            }
+            left = Filter::And(Box::new(left), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, ParseError> {
+        if self.keyword("NOT") {
+            return Ok(Filter::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Filter, ParseError> {
+        let Some(token) = self.tokens.get(self.pos) else {
+            return Err(ParseError::new(self.end, self.expected_filter()));
+        };
+        let column = token.column;
+        match &token.kind {
+            Kind::Open => {
+                self.pos += 1;
+                let filter = self.or()?;
+                match self.tokens.get(self.pos) {
+                    Some(Token { kind: Kind::Close, .. }) => {
+                        self.pos += 1;
+                        Ok(filter)
+                    }
+                    _ => Err(ParseError::new(column, "unclosed '('")),
+                }
+            }
+            Kind::Close => Err(ParseError::new(column, self.expected_filter())),
+            Kind::Atom { text, .. }
+                if self.is_keyword(self.pos, "AND") || self.is_keyword(self.pos, "OR") =>
+            {
+                Err(ParseError::new(column, format!("unexpected '{}'", text)))
+            }
+            Kind::Atom { text, bare } => {
+                let term = term(text, *bare, column)?;
+                self.pos += 1;
+                Ok(Filter::Term(term))
+            }
+        }
+    }
+
+    // "expected a filter", naming the keyword it should have followed
+    fn expected_filter(&self) -> String {
+        match self.pos.checked_sub(1).map(|i| &self.tokens[i].kind) {
+            Some(Kind::Atom { text, .. }) if self.is_keyword(self.pos - 1, "") => {
+                format!("expected a filter after '{}'", text)
+            }
+            Some(Kind::Open) => String::from("expected a filter after '('"),
+            _ => String::from("expected a filter"),
+        }
+    }
+
+    // whether the next token can start a filter that is implicitly ANDed
+    fn starts_filter(&self) -> bool {
+        match self.tokens.get(self.pos).map(|t| &t.kind) {
+            Some(Kind::Open) => true,
+            Some(Kind::Atom { .. }) => {
+                !self.is_keyword(self.pos, "AND") && !self.is_keyword(self.pos, "OR")
+            }
+            _ => false,
+        }
+    }
+
+    // consume the next token if it is the keyword
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(self.pos, keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    // whether the token at `pos` is the unquoted keyword, or any keyword when empty
+    fn is_keyword(&self, pos: usize, keyword: &str) -> bool {
+        match self.tokens.get(pos).map(|t| &t.kind) {
+            Some(Kind::Atom { text, bare }) if *bare == text.chars().count() => {
+                let upper = text.to_uppercase();
+                match keyword {
+                    "" => matches!(upper.as_str(), "AND" | "OR" | "NOT"),
+                    keyword => upper == keyword,
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+// a single `field:value` or `field<op>date` term, or a bare word to search for
+fn term(text: &str, bare: usize, column: usize) -> Result<Term, ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    let Some(at) = chars[..bare].iter().position(|c| ":<>=".contains(*c)) else {
+        return Ok(Term::Text(text.to_string()));
+    };
+    let field: String = chars[..at].iter().collect::<String>().to_lowercase();
+    let (cmp, len) = match (chars[at], chars.get(at + 1)) {
+        (':', _) => (None, 1),
+        ('<', Some('=')) => (Some(Cmp::Le), 2),
+        ('<', _) => (Some(Cmp::Lt), 1),
+        ('>', Some('=')) => (Some(Cmp::Ge), 2),
+        ('>', _) => (Some(Cmp::Gt), 1),
+        _ => (Some(Cmp::Eq), 1),
+    };
+    let op: String = chars[at..at + len].iter().collect();
+    let value: String = chars[at + len..].iter().collect();
+    let value_column = column + at + len;
+
+    if field.is_empty() {
+        return Err(ParseError::new(column, format!("missing field before '{}'", op)));
+    }
+    if value.trim().is_empty() {
+        return Err(ParseError::new(
+            value_column,
+            format!("missing value after '{}{}'", field, op),
+        ));
+    }
+    let date_field = match field.as_str() {
+        "created" => Some(DateField::Created),
+        "updated" => Some(DateField::Updated),
+        _ => None,
+    };
+    if let Some(date_field) = date_field {
+        let day = day(&value).ok_or_else(|| {
+            ParseError::new(
+                value_column,
+                format!("invalid date '{}', expected YYYY-MM-DD or days ago such as 30d", value),
+            )
+        })?;
+        return Ok(Term::Date(date_field, cmp.unwrap_or(Cmp::Eq), day));
+    }
+
+    if cmp.is_some() && ["tag", "notebook", "title", "text"].contains(&field.as_str()) {
+        return Err(ParseError::new(column + at, format!("'{}' only takes ':'", field)));
+    }
+    match field.as_str() {
+        "tag" => Ok(Term::Tag(value)),
+        "notebook" => Ok(Term::Notebook(value)),
+        "title" => Ok(Term::Title(value)),
+        "text" => Ok(Term::Text(value)),
+        _ => {
+            Err(ParseError::new(column, format!("unknown field '{}', expected {}", field, FIELDS)))
+        }
+    }
+}
+
+// `2026-01-01`, or `30d` for 30 days ago
+fn day(value: &str) -> Option<Day> {
+    if let Some(days) = value.strip_suffix(['d', 'D']) {
+        return days.parse().ok().map(Day::DaysAgo);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok().map(Day::On)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str) -> Filter {
+        Filter::Term(Term::Tag(name.to_string()))
+    }
+
+    fn and(left: Filter, right: Filter) -> Filter {
+        Filter::And(Box::new(left), Box::new(right))
+    }
+
+    fn or(left: Filter, right: Filter) -> Filter {
+        Filter::Or(Box::new(left), Box::new(right))
+    }
+
+    fn not(inner: Filter) -> Filter {
+        Filter::Not(Box::new(inner))
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(parse("tag:a OR tag:b AND tag:c"), Ok(or(tag("a"), and(tag("b"), tag("c")))));
+        assert_eq!(parse("tag:a AND tag:b OR tag:c"), Ok(or(and(tag("a"), tag("b")), tag("c"))));
+    }
+
+    #[test]
+    fn not_applies_to_the_next_term() {
+        assert_eq!(parse("NOT tag:a AND tag:b"), Ok(and(not(tag("a")), tag("b"))));
+        assert_eq!(parse("not not tag:a"), Ok(not(not(tag("a")))));
+    }
+
+    #[test]
+    fn parentheses_group() {
+        assert_eq!(parse("(tag:a OR tag:b) AND tag:c"), Ok(and(or(tag("a"), tag("b")), tag("c"))));
+        assert_eq!(parse("NOT (tag:a OR tag:b)"), Ok(not(or(tag("a"), tag("b")))));
+    }
+
+    #[test]
+    fn juxtaposed_terms_are_anded() {
+        assert_eq!(parse("tag:a tag:b"), Ok(and(tag("a"), tag("b"))));
+        assert_eq!(parse("tag:a (tag:b OR tag:c)"), Ok(and(tag("a"), or(tag("b"), tag("c")))));
+        assert_eq!(parse("tag:a tag:b OR tag:c"), Ok(or(and(tag("a"), tag("b")), tag("c"))));
+    }
+
+    #[test]
+    fn keywords_ignore_case() {
+        assert_eq!(parse("tag:a and tag:b"), Ok(and(tag("a"), tag("b"))));
+        assert_eq!(parse("tag:a Or tag:b"), Ok(or(tag("a"), tag("b"))));
+    }
+
+    #[test]
+    fn quotes_keep_spaces_and_keywords() {
+        assert_eq!(
+            parse(r#"title:"Box and Rc""#),
+            Ok(Filter::Term(Term::Title(String::from("Box and Rc"))))
+        );
+        assert_eq!(parse(r#""OR""#), Ok(Filter::Term(Term::Text(String::from("OR")))));
+        assert_eq!(parse(r#"tag:"a(b)""#), Ok(tag("a(b)")));
+        // a colon inside quotes is searched for, not read as a field
+        assert_eq!(parse(r#""std::vec""#), Ok(Filter::Term(Term::Text(String::from("std::vec")))));
+    }
+
+    #[test]
+    fn bare_words_search_the_text() {
+        assert_eq!(parse("iterator"), Ok(Filter::Term(Term::Text(String::from("iterator")))));
+    }
+
+    #[test]
+    fn dates_take_a_day_or_days_ago() {
+        let day = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        assert_eq!(
+            parse("updated>2026-01-01"),
+            Ok(Filter::Term(Term::Date(DateField::Updated, Cmp::Gt, Day::On(day))))
+        );
+        assert_eq!(
+            parse("created<=30d"),
+            Ok(Filter::Term(Term::Date(DateField::Created, Cmp::Le, Day::DaysAgo(30))))
+        );
+        assert_eq!(
+            parse("updated:7D"),
+            Ok(Filter::Term(Term::Date(DateField::Updated, Cmp::Eq, Day::DaysAgo(7))))
+        );
+        assert_eq!(
+            parse("created>=2026-01-01"),
+            Ok(Filter::Term(Term::Date(DateField::Created, Cmp::Ge, Day::On(day))))
+        );
+    }
+
+    fn column(query: &str) -> usize {
+        parse(query).unwrap_err().column
+    }
+
+    #[test]
+    fn errors_point_at_the_column() {
+        assert_eq!(parse(""), Err(ParseError::new(1, "empty query")));
+        assert_eq!(column("tag:a AND"), 10);
+        assert_eq!(column("tag:a OR OR tag:b"), 10);
+        assert_eq!(column(r#"tag:a title:"open"#), 13);
+        assert_eq!(column("tag:a (tag:b"), 7);
+        assert_eq!(column("tag:a )"), 7);
+        assert_eq!(column("tag:a updated>soon"), 15);
+        assert_eq!(column("tag:a colour:red"), 7);
+        assert_eq!(column("tag:a title<x"), 12);
+        assert_eq!(column("tag:"), 5);
+        assert_eq!(column(":a"), 1);
+    }
+
+    #[test]
+    fn errors_say_what_went_wrong() {
+        assert_eq!(parse("tag:a AND").unwrap_err().message, "expected a filter after 'AND'");
+        assert_eq!(parse("(tag:a").unwrap_err().message, "unclosed '('");
+        assert_eq!(parse(r#"title:"x"#).unwrap_err().message, "unterminated quote");
+        assert!(parse("foo:bar").unwrap_err().message.starts_with("unknown field 'foo'"));
+        assert_eq!(parse("title>x").unwrap_err().message, "'title' only takes ':'");
+    }
+
+    #[test]
+    fn pointer_marks_the_column() {
+        let query = "tag:a AND";
+        assert_eq!(parse(query).unwrap_err().pointer(query), "tag:a AND\n         ^");
+    }
+}
@@ -0,0 +1,89 @@
+// resolution of user-typed names to a single record, asking the user to pick
+// when a fuzzy lookup is ambiguous; commands that change or delete a record use
+// the `_exact` forms, which ask before acting on anything but an exact name
+use crate::crud::{note::*, notebook::*, tag::*};
+use crate::entities::{notebooks, notes, tags};
+use crate::names;
+use anyhow::{bail, Result};
+use sea_orm::DbConn;
+use std::io::{self, IsTerminal, Write};
+
+pub async fn note(db: &DbConn, title: &str) -> Result<notes::Model> {
+    let matches = find_notes_by_title(db, title).await?;
+    pick("notes", title, matches, |n| &n.note_name)
+}
+
+pub async fn notebook(db: &DbConn, name: &str) -> Result<notebooks::Model> {
+    let matches = find_notebooks_by_name(db, name).await?;
+    pick("notebooks", name, matches, |b| &b.notebook_name)
+}
+
+pub async fn tag(db: &DbConn, name: &str) -> Result<tags::Model> {
+    let matches = find_tags_by_name(db, name).await?;
+    pick("tags", name, matches, |t| &t.tag_name)
+}
+
+pub async fn note_exact(db: &DbConn, title: &str) -> Result<notes::Model> {
+    let matches = find_notes_by_title(db, title).await?;
+    exact("note", title, matches, |n| &n.note_name)
+}
+
+pub async fn notebook_exact(db: &DbConn, name: &str) -> Result<notebooks::Model> {
+    let matches = find_notebooks_by_name(db, name).await?;
+    exact("notebook", name, matches, |b| &b.notebook_name)
+}
+
+pub async fn tag_exact(db: &DbConn, name: &str) -> Result<tags::Model> {
+    let matches = find_tags_by_name(db, name).await?;
+    exact("tag", name, matches, |t| &t.tag_name)
+}
+
+// the match whose name equals `query` ignoring case, or a fuzzy one the user
+// confirms; without a terminal to ask on, only an exact name is accepted
+fn exact<T>(kind: &str, query: &str, mut matches: Vec<T>, name: impl Fn(&T) -> &str) -> Result<T> {
+    if let Some(i) = matches.iter().position(|m| names::fold(name(m)) == names::fold(query)) {
+        return Ok(matches.remove(i));
+    }
+    let names: Vec<&str> = matches.iter().map(&name).collect();
+    if !io::stdin().is_terminal() {
+        bail!(
+            "no {} named exactly '{}', closest: {}; use the full name",
+            kind,
+            query,
+            names.join(", ")
+        );
+    }
+    let chosen = pick(&format!("{}s", kind), query, matches, &name)?;
+    print!("'{}' is not an exact match, use {} '{}'? [y/N]: ", query, kind, name(&chosen));
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => Ok(chosen),
+        _ => bail!("no {} selected", kind),
+    }
+}
+
+fn pick<T>(kind: &str, query: &str, mut matches: Vec<T>, name: impl Fn(&T) -> &str) -> Result<T> {
+    if matches.len() == 1 {
+        return Ok(matches.remove(0));
+    }
+    let names: Vec<&str> = matches.iter().map(&name).collect();
+    if !io::stdin().is_terminal() {
+        bail!("'{}' matches several {}: {}", query, kind, names.join(", "));
+    }
+
+    println!("Several {} match '{}':", kind, query);
+    for (i, name) in names.iter().enumerate() {
+        println!("  {}) {}", i + 1, name);
+    }
+    print!("Select [1-{}]: ", names.len());
+    io::stdout().flush()?;
+
+    let mut choice = String::new();
+    io::stdin().read_line(&mut choice)?;
+    match choice.trim().parse::<usize>() {
+        Ok(i) if (1..=matches.len()).contains(&i) => Ok(matches.remove(i - 1)),
+        _ => bail!("no {} selected", kind),
+    }
+}
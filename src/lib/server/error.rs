@@ -0,0 +1,49 @@
+// mapping of crud errors to http responses
+use crate::names::EmptyName;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::{DbErr, SqlErr};
+use serde_json::json;
+
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    pub fn conflict(message: impl Into<String>) -> Self {
+        ApiError { status: StatusCode::CONFLICT, message: message.into() }
+    }
+}
+
+impl From<DbErr> for ApiError {
+    fn from(err: DbErr) -> Self {
+        let status = match (&err, err.sql_err()) {
+            (DbErr::RecordNotFound(_), _) => StatusCode::NOT_FOUND,
+            (_, Some(SqlErr::UniqueConstraintViolation(_)))
+            | (_, Some(SqlErr::ForeignKeyConstraintViolation(_))) => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let message = match err {
+            DbErr::RecordNotFound(message) => message,
+            err => err.to_string(),
+        };
+        ApiError { status, message }
+    }
+}
+
+// a name the models would reject, caught before it reaches the database
+impl From<EmptyName> for ApiError {
+    fn from(err: EmptyName) -> Self {
+        ApiError { status: StatusCode::UNPROCESSABLE_ENTITY, message: err.to_string() }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(json!({ "error": self.message }))).into_response()
+    }
+}
@@ -0,0 +1,228 @@
+// request handlers, each one a thin wrapper around the crud layer; names are
+// checked up front so an empty one is a client error
+use super::error::ApiError;
+use crate::crud::{note::*, notebook::*, tag::*};
+use crate::entities::{notebooks, notes, tags};
+use crate::names;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sea_orm::DatabaseConnection;
+use serde::Deserialize;
+
+type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Deserialize)]
+pub struct NotebookBody {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Deserialize)]
+pub struct NoteBody {
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+pub struct TagBody {
+    pub name: String,
+}
+
+// notebooks
+pub async fn list_notebooks(
+    State(db): State<DatabaseConnection>,
+) -> ApiResult<Json<Vec<notebooks::Model>>> {
+    Ok(Json(get_all_notebooks(&db).await?))
+}
+
+pub async fn create_notebook_handler(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<NotebookBody>,
+) -> ApiResult<(StatusCode, Json<notebooks::Model>)> {
+    let name = names::validate("notebook name", &body.name)?;
+    let notebook = create_notebook(&db, name, body.description).await?;
+    Ok((StatusCode::CREATED, Json(notebook)))
+}
+
+pub async fn get_notebook(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<notebooks::Model>> {
+    Ok(Json(get_notebook_by_id(&db, id).await?))
+}
+
+pub async fn update_notebook_handler(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+    Json(body): Json<NotebookBody>,
+) -> ApiResult<Json<notebooks::Model>> {
+    let name = names::validate("notebook name", &body.name)?;
+    Ok(Json(update_notebook(&db, id, name, body.description).await?))
+}
+
+pub async fn delete_notebook(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    get_notebook_by_id(&db, id).await?;
+    delete_notebook_by_id(&db, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// notebook memberships
+pub async fn list_notebook_notes(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<notes::Model>>> {
+    get_notebook_by_id(&db, id).await?;
+    Ok(Json(get_notes_in_notebook(&db, id).await?))
+}
+
+pub async fn add_notebook_note(
+    State(db): State<DatabaseConnection>,
+    Path((id, note_id)): Path<(i64, i64)>,
+) -> ApiResult<StatusCode> {
+    if get_notebook_by_id(&db, id).await?.filter.is_some() {
+        return Err(ApiError::conflict(format!("Notebook {} is a smart notebook", id)));
+    }
+    get_note_by_id(&db, note_id).await?;
+    if get_notes_in_notebook(&db, id).await?.iter().any(|n| n.id == note_id) {
+        return Err(ApiError::conflict(format!("Note {} is already in notebook {}", note_id, id)));
+    }
+    add_note_to_notebook(&db, id, note_id).await?;
+    Ok(StatusCode::CREATED)
+}
+
+pub async fn remove_notebook_note(
+    State(db): State<DatabaseConnection>,
+    Path((id, note_id)): Path<(i64, i64)>,
+) -> ApiResult<StatusCode> {
+    match remove_note_from_notebook(&db, id, note_id).await? {
+        0 => Err(sea_orm::DbErr::RecordNotFound(format!(
+            "Note {} is not in notebook {}",
+            note_id, id
+        ))
+        .into()),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+// notes
+pub async fn list_notes(
+    State(db): State<DatabaseConnection>,
+) -> ApiResult<Json<Vec<notes::Model>>> {
+    Ok(Json(get_all_notes(&db).await?))
+}
+
+pub async fn create_note_handler(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<NoteBody>,
+) -> ApiResult<(StatusCode, Json<notes::Model>)> {
+    let title = names::validate("note title", &body.title)?;
+    let note = create_note(&db, title, body.content).await?;
+    Ok((StatusCode::CREATED, Json(note)))
+}
+
+pub async fn get_note(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<notes::Model>> {
+    Ok(Json(get_note_by_id(&db, id).await?))
+}
+
+pub async fn update_note_handler(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+    Json(body): Json<NoteBody>,
+) -> ApiResult<Json<notes::Model>> {
+    let title = names::validate("note title", &body.title)?;
+    Ok(Json(update_note(&db, id, title, body.content).await?))
+}
+
+pub async fn delete_note(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    get_note_by_id(&db, id).await?;
+    delete_note_by_id(&db, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// note tags
+pub async fn list_note_tags(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<Vec<tags::Model>>> {
+    get_note_by_id(&db, id).await?;
+    Ok(Json(get_tags_for_note(&db, id).await?))
+}
+
+pub async fn add_note_tag(
+    State(db): State<DatabaseConnection>,
+    Path((id, tag_id)): Path<(i64, i64)>,
+) -> ApiResult<StatusCode> {
+    get_note_by_id(&db, id).await?;
+    get_tag_by_id(&db, tag_id).await?;
+    if get_tags_for_note(&db, id).await?.iter().any(|t| t.id == tag_id) {
+        return Err(ApiError::conflict(format!("Note {} is already tagged {}", id, tag_id)));
+    }
+    add_tag_to_note(&db, id, tag_id).await?;
+    Ok(StatusCode::CREATED)
+}
+
+pub async fn remove_note_tag(
+    State(db): State<DatabaseConnection>,
+    Path((id, tag_id)): Path<(i64, i64)>,
+) -> ApiResult<StatusCode> {
+    match remove_tag_from_note(&db, id, tag_id).await? {
+        0 => {
+            Err(sea_orm::DbErr::RecordNotFound(format!("Note {} is not tagged {}", id, tag_id))
+                .into())
+        }
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+// tags
+pub async fn list_tags(State(db): State<DatabaseConnection>) -> ApiResult<Json<Vec<tags::Model>>> {
+    Ok(Json(get_all_tags(&db).await?))
+}
+
+pub async fn create_tag_handler(
+    State(db): State<DatabaseConnection>,
+    Json(body): Json<TagBody>,
+) -> ApiResult<(StatusCode, Json<tags::Model>)> {
+    let name = names::validate("tag name", &body.name)?;
+    let tag = create_tag(&db, name).await?;
+    Ok((StatusCode::CREATED, Json(tag)))
+}
+
+pub async fn get_tag(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<tags::Model>> {
+    Ok(Json(get_tag_by_id(&db, id).await?))
+}
+
+pub async fn update_tag(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+    Json(body): Json<TagBody>,
+) -> ApiResult<Json<tags::Model>> {
+    let name = names::validate("tag name", &body.name)?;
+    Ok(Json(rename_tag(&db, id, name).await?))
+}
+
+pub async fn delete_tag(
+    State(db): State<DatabaseConnection>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    get_tag_by_id(&db, id).await?;
+    delete_tag_by_id(&db, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,37 @@
+// local http json api over the crud layer
+mod error;
+mod handlers;
+
+use anyhow::Result;
+use axum::{routing::get, routing::put, Router};
+use handlers::*;
+use sea_orm::DatabaseConnection;
+use std::net::SocketAddr;
+
+pub fn router(db: DatabaseConnection) -> Router {
+    Router::new()
+        .route("/notebooks", get(list_notebooks).post(create_notebook_handler))
+        .route(
+            "/notebooks/{id}",
+            get(get_notebook).put(update_notebook_handler).delete(delete_notebook),
+        )
+        .route("/notebooks/{id}/notes", get(list_notebook_notes))
+        .route(
+            "/notebooks/{id}/notes/{note_id}",
+            put(add_notebook_note).delete(remove_notebook_note),
+        )
+        .route("/notes", get(list_notes).post(create_note_handler))
+        .route("/notes/{id}", get(get_note).put(update_note_handler).delete(delete_note))
+        .route("/notes/{id}/tags", get(list_note_tags))
+        .route("/notes/{id}/tags/{tag_id}", put(add_note_tag).delete(remove_note_tag))
+        .route("/tags", get(list_tags).post(create_tag_handler))
+        .route("/tags/{id}", get(get_tag).put(update_tag).delete(delete_tag))
+        .with_state(db)
+}
+
+pub async fn serve(db: DatabaseConnection, bind: SocketAddr) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    println!("Serving modnote API on http://{}", listener.local_addr()?);
+    axum::serve(listener, router(db)).await?;
+    Ok(())
+}
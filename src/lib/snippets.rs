@@ -0,0 +1,376 @@
+// extraction, compilation and execution of fenced rust code blocks in notes
+use anyhow::{Context, Result};
+use pulldown_cmark::{CodeBlockKind, Event, Parser, Tag, TagEnd};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+// rustdoc-style annotations on a fence such as ```rust,no_run
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FenceAttrs {
+    pub ignore: bool,
+    pub no_run: bool,
+    pub should_panic: bool,
+    pub compile_fail: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Snippet {
+    // 1-based position among the rust blocks of the note
+    pub index: usize,
+    pub code: String,
+    pub attrs: FenceAttrs,
+    // byte range of the whole fenced block within the note content
+    pub range: Range<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Ignored,
+    Failed(String),
+}
+
+pub struct RunOutput {
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+}
+
+// parse a fence info string, returning `None` for blocks that are not rust
+pub fn parse_fence(info: &str) -> Option<FenceAttrs> {
+    let mut attrs = FenceAttrs::default();
+    let mut is_rust = false;
+    for token in info.split([',', ' ']).map(str::trim).filter(|t| !t.is_empty()) {
+        match token {
+            "rust" => is_rust = true,
+            "ignore" => attrs.ignore = true,
+            "no_run" => attrs.no_run = true,
+            "should_panic" => attrs.should_panic = true,
+            "compile_fail" => attrs.compile_fail = true,
+            t if t.starts_with("edition") => {}
+            _ => return None,
+        }
+    }
+    // bare annotations such as ```no_run imply rust, like in doc comments
+    (is_rust || attrs != FenceAttrs::default()).then_some(attrs)
+}
+
+pub fn extract(content: &str) -> Vec<Snippet> {
+    let mut snippets = Vec::new();
+    let mut current: Option<(FenceAttrs, String)> = None;
+
+    for (event, range) in Parser::new(content).into_offset_iter() {
+        match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                current = parse_fence(&info).map(|attrs| (attrs, String::new()));
+            }
+            Event::Text(text) => {
+                if let Some((_, code)) = current.as_mut() {
+                    code.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((attrs, code)) = current.take() {
+                    snippets.push(Snippet { index: snippets.len() + 1, code, attrs, range });
+                }
+            }
+            _ => {}
+        }
+    }
+    snippets
+}
+
+// wrap a snippet the way rustdoc wraps doctests: unhide `# ` lines, keep crate
+// attributes at the top and put everything else in `fn main` unless it has one
+pub fn wrap(code: &str) -> String {
+    let lines: Vec<&str> = code
+        .lines()
+        .map(|line| match line.trim_start() {
+            "#" => "",
+            l if l.starts_with("# ") => &l[2..],
+            _ => line,
+        })
+        .collect();
+
+    let mut crate_attrs = String::from("#![allow(unused)]\n");
+    let mut body = String::new();
+    for line in lines {
+        if line.trim_start().starts_with("#![") {
+            crate_attrs.push_str(line);
+            crate_attrs.push('\n');
+        } else {
+            body.push_str(line);
+            body.push('\n');
+        }
+    }
+
+    if body.contains("fn main") {
+        format!("{}{}", crate_attrs, body)
+    } else {
+        format!("{}fn main() {{\n{}}}\n", crate_attrs, body)
+    }
+}
+
+// compile the wrapped snippet with the local rustc, returning the binary path
+// or the compiler diagnostics
+pub async fn compile(snippet: &Snippet, dir: &Path) -> Result<Result<PathBuf, String>> {
+    let source = dir.join(format!("snippet_{}.rs", snippet.index));
+    let binary = dir.join(format!("snippet_{}{}", snippet.index, std::env::consts::EXE_SUFFIX));
+    std::fs::write(&source, wrap(&snippet.code))?;
+
+    let output = Command::new("rustc")
+        .args(["--edition", "2021", "--crate-name", "snippet", "-o"])
+        .arg(&binary)
+        .arg(&source)
+        .output()
+        .await
+        .context("failed to run rustc, is a Rust toolchain installed?")?;
+
+    if output.status.success() {
+        Ok(Ok(binary))
+    } else {
+        Ok(Err(String::from_utf8_lossy(&output.stderr).into_owned()))
+    }
+}
+
+pub async fn run(binary: &Path, timeout: Duration) -> Result<RunOutput> {
+    let mut child = Command::new(binary)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    // the pipes are read while the snippet runs, so what it printed before a
+    // timeout is kept
+    let stdout = read_pipe(child.stdout.take());
+    let stderr = read_pipe(child.stderr.take());
+
+    let (status, timed_out) = match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => (status?.code(), false),
+        Err(_) => {
+            child.kill().await?;
+            (None, true)
+        }
+    };
+    Ok(RunOutput {
+        status,
+        stdout: String::from_utf8_lossy(&stdout.await?).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr.await?).into_owned(),
+        timed_out,
+    })
+}
+
+fn read_pipe<R: AsyncRead + Unpin + Send + 'static>(pipe: Option<R>) -> JoinHandle<Vec<u8>> {
+    tokio::spawn(async move {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            // a read error ends the output early rather than failing the run
+            let _ = pipe.read_to_end(&mut buf).await;
+        }
+        buf
+    })
+}
+
+// check a snippet like a doctest: compile it, then run it unless it is `no_run`
+pub async fn check(snippet: &Snippet, dir: &Path, timeout: Duration) -> Result<Outcome> {
+    if snippet.attrs.ignore {
+        return Ok(Outcome::Ignored);
+    }
+
+    let binary = match compile(snippet, dir).await? {
+        Ok(_) if snippet.attrs.compile_fail => {
+            return Ok(Outcome::Failed(String::from("compiled successfully but should fail")))
+        }
+        Ok(binary) => binary,
+        Err(_) if snippet.attrs.compile_fail => return Ok(Outcome::Passed),
+        Err(stderr) => return Ok(Outcome::Failed(stderr)),
+    };
+    if snippet.attrs.no_run {
+        return Ok(Outcome::Passed);
+    }
+
+    let output = run(&binary, timeout).await?;
+    let outcome = match (output.timed_out, output.status, snippet.attrs.should_panic) {
+        (true, _, _) => Outcome::Failed(format!("timed out after {}s", timeout.as_secs())),
+        (false, Some(0), false) => Outcome::Passed,
+        (false, Some(0), true) => {
+            Outcome::Failed(String::from("ran successfully but should panic"))
+        }
+        (false, _, true) => Outcome::Passed,
+        (false, _, false) => Outcome::Failed(output.stderr),
+    };
+    Ok(outcome)
+}
+
+// write the output of a run into the content as an ```output block right after
+// the snippet, replacing the block left by an earlier run
+pub fn save_output(content: &str, snippet: &Snippet, output: &RunOutput) -> String {
+    let mut block = String::from("```output\n");
+    block.push_str(&output.stdout);
+    if !output.stderr.is_empty() {
+        if !block.ends_with('\n') {
+            block.push('\n');
+        }
+        block.push_str(&output.stderr);
+    }
+    if !block.ends_with('\n') {
+        block.push('\n');
+    }
+    block.push_str("```");
+
+    let rest = &content[snippet.range.end..];
+    let gap = rest.len() - rest.trim_start().len();
+    let previous = Parser::new(rest).into_offset_iter().find_map(|(event, range)| match event {
+        Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) if range.start == gap => {
+            (info.trim() == "output").then_some(range)
+        }
+        _ => None,
+    });
+
+    let tail = match previous {
+        Some(range) => &rest[range.end..],
+        None => rest,
+    };
+    let head = content[..snippet.range.end].trim_end_matches('\n');
+    match tail.trim_start_matches('\n') {
+        "" => format!("{}\n\n{}\n", head, block),
+        tail => format!("{}\n\n{}\n\n{}", head, block, tail),
+    }
+}
+
+// check every rust block of a note in a fresh temp dir
+pub async fn check_content(content: &str, timeout: Duration) -> Result<Vec<(Snippet, Outcome)>> {
+    let dir = tempfile::tempdir()?;
+    let mut results = Vec::new();
+    for snippet in extract(content) {
+        let outcome = check(&snippet, dir.path(), timeout).await?;
+        results.push((snippet, outcome));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(stdout: &str, stderr: &str) -> RunOutput {
+        RunOutput {
+            status: Some(0),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn wrap_puts_the_code_in_main() {
+        assert_eq!(
+            wrap("let v = vec![1];\nassert_eq!(v.len(), 1);\n"),
+            "#![allow(unused)]\nfn main() {\nlet v = vec![1];\nassert_eq!(v.len(), 1);\n}\n"
+        );
+    }
+
+    #[test]
+    fn wrap_keeps_a_main_of_its_own() {
+        assert_eq!(
+            wrap("fn main() {\n    println!(\"hi\");\n}\n"),
+            "#![allow(unused)]\nfn main() {\n    println!(\"hi\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn wrap_unhides_hidden_lines() {
+        assert_eq!(
+            wrap("# use std::rc::Rc;\n#\nlet a = Rc::new(1);\n"),
+            "#![allow(unused)]\nfn main() {\nuse std::rc::Rc;\n\nlet a = Rc::new(1);\n}\n"
+        );
+        // an attribute is not a hidden line
+        assert!(wrap("#[derive(Debug)]\nstruct A;\n").contains("\n#[derive(Debug)]\nstruct A;"));
+    }
+
+    #[test]
+    fn wrap_lifts_crate_attributes_out_of_main() {
+        assert_eq!(
+            wrap("#![feature(never_type)]\nlet x = 1;\n"),
+            "#![allow(unused)]\n#![feature(never_type)]\nfn main() {\nlet x = 1;\n}\n"
+        );
+    }
+
+    #[test]
+    fn fence_annotations_are_read() {
+        assert_eq!(parse_fence("rust"), Some(FenceAttrs::default()));
+        assert_eq!(
+            parse_fence("rust,no_run"),
+            Some(FenceAttrs { no_run: true, ..FenceAttrs::default() })
+        );
+        assert_eq!(
+            parse_fence("should_panic"),
+            Some(FenceAttrs { should_panic: true, ..FenceAttrs::default() })
+        );
+        assert_eq!(
+            parse_fence("rust ignore edition2021"),
+            Some(FenceAttrs { ignore: true, ..FenceAttrs::default() })
+        );
+        assert_eq!(parse_fence(""), None);
+        assert_eq!(parse_fence("python"), None);
+        assert_eq!(parse_fence("output"), None);
+    }
+
+    #[test]
+    fn extract_numbers_the_rust_blocks() {
+        let content =
+            "```rust\nlet a = 1;\n```\n\n```python\nprint(1)\n```\n\n```no_run\nloop {}\n```\n";
+        let snippets = extract(content);
+        assert_eq!(snippets.len(), 2);
+        assert_eq!((snippets[0].index, snippets[0].code.as_str()), (1, "let a = 1;\n"));
+        assert_eq!((snippets[1].index, snippets[1].code.as_str()), (2, "loop {}\n"));
+        assert!(snippets[1].attrs.no_run);
+        assert_eq!(&content[snippets[0].range.clone()], "```rust\nlet a = 1;\n```");
+    }
+
+    #[test]
+    fn save_output_adds_a_block_after_the_snippet() {
+        let content = "intro\n\n```rust\nprintln!(\"1\");\n```\n\nafter\n";
+        let snippet = &extract(content)[0];
+        assert_eq!(
+            save_output(content, snippet, &output("1\n", "")),
+            "intro\n\n```rust\nprintln!(\"1\");\n```\n\n```output\n1\n```\n\nafter\n"
+        );
+    }
+
+    #[test]
+    fn save_output_replaces_an_earlier_output_block() {
+        let content = "```rust\nprintln!(\"2\");\n```\n\n```output\n1\n```\n\nafter\n";
+        let snippet = &extract(content)[0];
+        assert_eq!(
+            save_output(content, snippet, &output("2\n", "")),
+            "```rust\nprintln!(\"2\");\n```\n\n```output\n2\n```\n\nafter\n"
+        );
+    }
+
+    #[test]
+    fn save_output_leaves_other_blocks_alone() {
+        let content = "```rust\nlet a = 1;\n```\n\n```text\nnotes\n```\n";
+        let snippet = &extract(content)[0];
+        assert_eq!(
+            save_output(content, snippet, &output("", "")),
+            "```rust\nlet a = 1;\n```\n\n```output\n```\n\n```text\nnotes\n```\n"
+        );
+    }
+
+    #[test]
+    fn save_output_appends_stderr_on_its_own_line() {
+        let content = "```rust\nx\n```";
+        let snippet = &extract(content)[0];
+        assert_eq!(
+            save_output(content, snippet, &output("out", "err\n")),
+            "```rust\nx\n```\n\n```output\nout\nerr\n```\n"
+        );
+    }
+}
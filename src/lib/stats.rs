@@ -0,0 +1,149 @@
+// counts and aggregates over the whole database, computed in sql so they stay
+// cheap as the notes grow
+use crate::entities::prelude::{NoteStatusHistory, NotebookNotes, Notebooks, Notes, Tags};
+use crate::entities::{note_status_history, note_tags, notebook_notes, notebooks, notes, tags};
+use chrono::NaiveDateTime;
+use sea_orm::sea_query::{Expr, Func, Query};
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, JoinType, Order, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, RelationTrait,
+};
+use serde::Serialize;
+
+// how many notes and largest notes to list
+const TOP: u64 = 5;
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub notebooks: u64,
+    pub notes: u64,
+    pub tags: u64,
+    // notes filed in each notebook, smart notebooks left out
+    pub notes_per_notebook: Vec<Count>,
+    pub notes_per_tag: Vec<Count>,
+    // notes filed in no notebook
+    pub orphan_notes: Vec<String>,
+    pub unused_tags: Vec<String>,
+    // average length of the note content, in characters
+    pub average_length: f64,
+    pub largest_notes: Vec<Count>,
+    pub recent: Recent,
+}
+
+// a name with a number: notes, or characters for the largest notes
+#[derive(Debug, Serialize)]
+pub struct Count {
+    pub name: String,
+    pub count: i64,
+}
+
+// what happened since a point in time
+#[derive(Debug, Serialize)]
+pub struct Recent {
+    pub since: NaiveDateTime,
+    pub created: u64,
+    pub updated: u64,
+    pub status_changes: u64,
+    // the notes updated last, newest first
+    pub last_updated: Vec<String>,
+}
+
+pub async fn collect<C: ConnectionTrait>(db: &C, since: NaiveDateTime) -> Result<Stats, DbErr> {
+    let notes_per_notebook: Vec<Count> = Notebooks::find()
+        .select_only()
+        .column(notebooks::Column::NotebookName)
+        .column_as(notebook_notes::Column::NoteId.count(), "count")
+        .join(JoinType::LeftJoin, notebooks::Relation::NotebookNotes.def())
+        .filter(notebooks::Column::Filter.is_null())
+        .group_by(notebooks::Column::Id)
+        .order_by(Expr::col("count"), Order::Desc)
+        .order_by_asc(notebooks::Column::NotebookName)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+
+    let notes_per_tag: Vec<Count> = Tags::find()
+        .select_only()
+        .column(tags::Column::TagName)
+        .column_as(note_tags::Column::NoteId.count(), "count")
+        .join(JoinType::LeftJoin, tags::Relation::NoteTags.def())
+        .group_by(tags::Column::Id)
+        .order_by(Expr::col("count"), Order::Desc)
+        .order_by_asc(tags::Column::TagName)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+    let unused_tags: Vec<String> =
+        notes_per_tag.iter().filter(|t| t.count == 0).map(|t| t.name.clone()).collect();
+
+    let filed =
+        Query::select().column(notebook_notes::Column::NoteId).from(NotebookNotes).to_owned();
+    let orphan_notes = Notes::find()
+        .select_only()
+        .column(notes::Column::NoteName)
+        .filter(notes::Column::Id.not_in_subquery(filed))
+        .order_by_asc(notes::Column::NoteName)
+        .into_tuple::<String>()
+        .all(db)
+        .await?;
+
+    let length = Func::char_length(Expr::col(notes::Column::Content));
+    let average_length = Notes::find()
+        .select_only()
+        .column_as(Expr::expr(Func::avg(length.clone())), "average")
+        .into_tuple::<Option<f64>>()
+        .one(db)
+        .await?
+        .flatten()
+        .unwrap_or_default();
+    let largest_notes = Notes::find()
+        .select_only()
+        .column(notes::Column::NoteName)
+        .column_as(Expr::expr(length), "length")
+        .order_by(Expr::col("length"), Order::Desc)
+        .limit(TOP)
+        .into_tuple::<(String, i64)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+
+    let recent = Recent {
+        since,
+        created: Notes::find().filter(notes::Column::CreatedAt.gte(since)).count(db).await?,
+        updated: Notes::find().filter(notes::Column::UpdatedAt.gte(since)).count(db).await?,
+        status_changes: NoteStatusHistory::find()
+            .filter(note_status_history::Column::ChangedAt.gte(since))
+            .count(db)
+            .await?,
+        last_updated: Notes::find()
+            .select_only()
+            .column(notes::Column::NoteName)
+            .filter(notes::Column::UpdatedAt.gte(since))
+            .order_by_desc(notes::Column::UpdatedAt)
+            .limit(TOP)
+            .into_tuple::<String>()
+            .all(db)
+            .await?,
+    };
+
+    Ok(Stats {
+        notebooks: Notebooks::find().count(db).await?,
+        notes: Notes::find().count(db).await?,
+        tags: Tags::find().count(db).await?,
+        notes_per_notebook,
+        notes_per_tag,
+        orphan_notes,
+        unused_tags,
+        average_length,
+        largest_notes,
+        recent,
+    })
+}
@@ -0,0 +1,316 @@
+// note templates: the built-ins below and markdown files in the templates
+// directory next to the config file, which take precedence over a built-in of
+// the same name; `{{title}}`, `{{path}}`, `{{date}}` and `{{notebook}}` are
+// filled in when a note is created from one
+use crate::config;
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const BUILTINS: [(&str, &str); 5] = [
+    ("keyword", KEYWORD),
+    ("std-macro", STD_MACRO),
+    ("std-module", STD_MODULE),
+    ("std-trait", STD_TRAIT),
+    ("std-type", STD_TYPE),
+];
+
+pub struct Template {
+    pub name: String,
+    pub text: String,
+    // the file it was read from, none for a built-in
+    pub file: Option<PathBuf>,
+}
+
+// values substituted for the template variables
+pub struct Vars<'a> {
+    pub title: &'a str,
+    // path of the std item, e.g. std::vec::Vec
+    pub path: &'a str,
+    pub date: NaiveDate,
+    pub notebook: Option<&'a str>,
+}
+
+// every template, by name
+pub fn list() -> Result<Vec<Template>> {
+    let mut templates: BTreeMap<String, Template> = BUILTINS
+        .iter()
+        .map(|(name, text)| {
+            let template = Template { name: name.to_string(), text: text.to_string(), file: None };
+            (name.to_string(), template)
+        })
+        .collect();
+    let Some(dir) = dir() else {
+        return Ok(templates.into_values().collect());
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(templates.into_values().collect())
+        }
+        Err(e) => return Err(e).with_context(|| format!("can't read {}", dir.display())),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+        let Some(name) = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        let text =
+            fs::read_to_string(&path).with_context(|| format!("can't read {}", path.display()))?;
+        templates.insert(name.clone(), Template { name, text, file: Some(path) });
+    }
+    Ok(templates.into_values().collect())
+}
+
+pub fn get(name: &str) -> Result<Template> {
+    let mut templates = list()?;
+    match templates.iter().position(|t| t.name == name) {
+        Some(i) => Ok(templates.swap_remove(i)),
+        None => {
+            let names: Vec<String> = templates.into_iter().map(|t| t.name).collect();
+            bail!("no template named '{}', expected one of {}", name, names.join(", "))
+        }
+    }
+}
+
+// write a template to the templates directory, overriding a built-in of the
+// same name
+pub fn save(name: &str, text: &str) -> Result<PathBuf> {
+    let path = file(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, text).with_context(|| format!("can't write {}", path.display()))?;
+    Ok(path)
+}
+
+// delete a template from the templates directory; built-ins can't be removed
+pub fn remove(name: &str) -> Result<PathBuf> {
+    let path = file(name)?;
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => match is_builtin(name) {
+            true => bail!("'{}' is a built-in template and can't be removed", name),
+            false => bail!("no template named '{}' in {}", name, path.display()),
+        },
+        Err(e) => Err(e).with_context(|| format!("can't remove {}", path.display())),
+    }
+}
+
+pub fn is_builtin(name: &str) -> bool {
+    BUILTINS.iter().any(|(builtin, _)| *builtin == name)
+}
+
+// the template with its variables filled in; unknown ones are left as written
+pub fn render(text: &str, vars: &Vars) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else { break };
+        let variable = &rest[start + 2..start + end];
+        out.push_str(&rest[..start]);
+        match variable.trim() {
+            "title" => out.push_str(vars.title),
+            "path" => out.push_str(vars.path),
+            "date" => out.push_str(&vars.date.to_string()),
+            "notebook" => out.push_str(vars.notebook.unwrap_or_default()),
+            _ => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &rest[start + end + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// the templates directory, next to the config file
+pub fn dir() -> Option<PathBuf> {
+    config::path()?.parent().map(|dir| dir.join("templates"))
+}
+
+fn file(name: &str) -> Result<PathBuf> {
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        bail!("invalid template name '{}', use letters, digits, '-' and '_'", name);
+    }
+    let dir =
+        dir().context("can't locate the templates directory, set $HOME or $MODNOTE_CONFIG")?;
+    Ok(dir.join(format!("{}.md", name)))
+}
+
+const STD_TYPE: &str = "`{{path}}` ([docs](https://doc.rust-lang.org/std/?search={{path}}))
+
+## Signature
+
+```rust,ignore
+```
+
+## Key methods
+
+-
+
+## Trait impls
+
+-
+
+## Gotchas
+
+-
+
+## Example
+
+```rust
+```
+
+_Written {{date}}_
+";
+
+const STD_TRAIT: &str = "`{{path}}` ([docs](https://doc.rust-lang.org/std/?search={{path}}))
+
+## Definition
+
+```rust,ignore
+```
+
+## Required methods
+
+-
+
+## Provided methods
+
+-
+
+## Notable implementors
+
+-
+
+## Gotchas
+
+-
+
+## Example
+
+```rust
+```
+
+_Written {{date}}_
+";
+
+const STD_MACRO: &str = "`{{path}}!` ([docs](https://doc.rust-lang.org/std/?search={{path}}))
+
+## Syntax
+
+```rust,ignore
+```
+
+## Expands to
+
+-
+
+## Gotchas
+
+-
+
+## Example
+
+```rust
+```
+
+_Written {{date}}_
+";
+
+const STD_MODULE: &str = "`{{path}}` ([docs](https://doc.rust-lang.org/std/?search={{path}}))
+
+## Purpose
+
+-
+
+## Key items
+
+-
+
+## Example
+
+```rust
+```
+
+_Written {{date}}_
+";
+
+const KEYWORD: &str = "`{{title}}` ([docs](https://doc.rust-lang.org/std/keyword.{{title}}.html))
+
+## Meaning
+
+-
+
+## Where it appears
+
+-
+
+## Gotchas
+
+-
+
+## Example
+
+```rust
+```
+
+_Written {{date}}_
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(notebook: Option<&str>) -> Vars<'_> {
+        Vars {
+            title: "Vec",
+            path: "std::vec::Vec",
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            notebook,
+        }
+    }
+
+    #[test]
+    fn fills_in_every_variable() {
+        assert_eq!(
+            render("# {{title}}\n`{{path}}` on {{date}} in {{notebook}}\n", &vars(Some("std"))),
+            "# Vec\n`std::vec::Vec` on 2026-10-19 in std\n"
+        );
+    }
+
+    #[test]
+    fn variables_may_be_spaced_and_repeated() {
+        assert_eq!(render("{{ title }} / {{title}}", &vars(None)), "Vec / Vec");
+    }
+
+    #[test]
+    fn no_notebook_leaves_it_empty() {
+        assert_eq!(render("in [{{notebook}}]", &vars(None)), "in []");
+    }
+
+    #[test]
+    fn unknown_and_unclosed_variables_are_left_as_written() {
+        assert_eq!(render("{{author}} wrote {{title}}", &vars(None)), "{{author}} wrote Vec");
+        assert_eq!(render("{{title}} {{date", &vars(None)), "Vec {{date");
+        assert_eq!(render("no variables", &vars(None)), "no variables");
+    }
+
+    #[test]
+    fn substituted_text_is_not_expanded_again() {
+        let vars = Vars { title: "{{path}}", ..vars(None) };
+        assert_eq!(render("{{title}}", &vars), "{{path}}");
+    }
+
+    #[test]
+    fn builtins_use_only_known_variables() {
+        for (name, text) in BUILTINS {
+            assert!(!render(text, &vars(Some("std"))).contains("{{"), "{} left a variable", name);
+        }
+    }
+}
@@ -0,0 +1,412 @@
+// application state and key handling for the tui browser
+use crate::crud::{note::*, notebook::*, tag::*};
+use crate::entities::{notebooks, notes, tags};
+use crate::names;
+use anyhow::Result;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::ListState;
+use sea_orm::{DbConn, TransactionTrait};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Focus {
+    Notebooks,
+    Notes,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputAction {
+    NewNotebook,
+    NewNote,
+    RenameNotebook,
+    RenameNote,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Normal,
+    Search,
+    Input(InputAction),
+    ConfirmDelete,
+}
+
+pub struct App {
+    pub focus: Focus,
+    pub mode: Mode,
+    pub running: bool,
+    // index 0 of the notebooks pane is the "All notes" entry
+    pub notebooks: Vec<notebooks::Model>,
+    pub notebook_state: ListState,
+    pub notes: Vec<notes::Model>,
+    pub note_state: ListState,
+    // indexes into `notes` that pass the search query and tag filter
+    pub visible: Vec<usize>,
+    pub tags: Vec<tags::Model>,
+    pub tag_filter: Option<usize>,
+    tagged_note_ids: HashSet<i64>,
+    pub preview_tags: Vec<String>,
+    pub search: String,
+    pub input: String,
+    pub status: String,
+}
+
+impl App {
+    pub async fn new(db: &DbConn) -> Result<Self> {
+        let mut app = App {
+            focus: Focus::Notebooks,
+            mode: Mode::Normal,
+            running: true,
+            notebooks: Vec::new(),
+            notebook_state: ListState::default().with_selected(Some(0)),
+            notes: Vec::new(),
+            note_state: ListState::default(),
+            visible: Vec::new(),
+            tags: Vec::new(),
+            tag_filter: None,
+            tagged_note_ids: HashSet::new(),
+            preview_tags: Vec::new(),
+            search: String::new(),
+            input: String::new(),
+            status: String::from("? for help"),
+        };
+        app.reload(db).await?;
+        Ok(app)
+    }
+
+    // the currently selected notebook, `None` when "All notes" is selected
+    pub fn selected_notebook(&self) -> Option<&notebooks::Model> {
+        match self.notebook_state.selected() {
+            Some(i) if i > 0 => self.notebooks.get(i - 1),
+            _ => None,
+        }
+    }
+
+    pub fn selected_note(&self) -> Option<&notes::Model> {
+        self.note_state.selected().and_then(|i| self.visible.get(i)).map(|&i| &self.notes[i])
+    }
+
+    pub fn tag_filter_name(&self) -> Option<&str> {
+        self.tag_filter.and_then(|i| self.tags.get(i)).map(|t| t.tag_name.as_str())
+    }
+
+    // reload every pane from the database
+    pub async fn reload(&mut self, db: &DbConn) -> Result<()> {
+        self.notebooks = get_all_notebooks(db).await?;
+        self.tags = get_all_tags(db).await?;
+        if self.tag_filter.is_some_and(|i| i >= self.tags.len()) {
+            self.tag_filter = None;
+        }
+        let selected = self.notebook_state.selected().unwrap_or(0).min(self.notebooks.len());
+        self.notebook_state.select(Some(selected));
+        self.load_notes(db).await
+    }
+
+    // load the notes of the selected notebook and reapply the filters
+    async fn load_notes(&mut self, db: &DbConn) -> Result<()> {
+        self.notes = match self.selected_notebook() {
+            Some(book) => get_notes_in_notebook(db, book.id).await?,
+            None => get_all_notes(db).await?,
+        };
+        self.tagged_note_ids = match self.tag_filter.and_then(|i| self.tags.get(i)) {
+            Some(tag) => get_notes_with_tag(db, tag.id).await?.into_iter().map(|n| n.id).collect(),
+            None => HashSet::new(),
+        };
+        self.apply_filters(db).await
+    }
+
+    // recompute the visible notes from the search query and tag filter
+    async fn apply_filters(&mut self, db: &DbConn) -> Result<()> {
+        let query = self.search.to_lowercase();
+        let tag_filter = self.tag_filter.is_some();
+        self.visible = self
+            .notes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !tag_filter || self.tagged_note_ids.contains(&n.id))
+            .filter(|(_, n)| {
+                query.is_empty()
+                    || n.note_name.to_lowercase().contains(&query)
+                    || n.content.to_lowercase().contains(&query)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let selected = match self.note_state.selected() {
+            _ if self.visible.is_empty() => None,
+            Some(i) => Some(i.min(self.visible.len() - 1)),
+            None => Some(0),
+        };
+        self.note_state.select(selected);
+        self.load_preview(db).await
+    }
+
+    async fn load_preview(&mut self, db: &DbConn) -> Result<()> {
+        self.preview_tags = match self.selected_note() {
+            Some(note) => {
+                get_tags_for_note(db, note.id).await?.into_iter().map(|t| t.tag_name).collect()
+            }
+            None => Vec::new(),
+        };
+        Ok(())
+    }
+
+    pub async fn handle_key(&mut self, db: &DbConn, key: KeyEvent) -> Result<()> {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.running = false;
+            return Ok(());
+        }
+        match self.mode.clone() {
+            Mode::Normal => self.handle_normal_key(db, key).await?,
+            Mode::Search => self.handle_search_key(db, key).await?,
+            Mode::Input(action) => self.handle_input_key(db, key, action).await?,
+            Mode::ConfirmDelete => self.handle_confirm_key(db, key).await?,
+        }
+        Ok(())
+    }
+
+    async fn handle_normal_key(&mut self, db: &DbConn, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.running = false,
+            KeyCode::Tab
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Char('h')
+            | KeyCode::Char('l') => {
+                self.focus = match self.focus {
+                    Focus::Notebooks => Focus::Notes,
+                    Focus::Notes => Focus::Notebooks,
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(db, 1).await?,
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(db, -1).await?,
+            KeyCode::Char('/') => {
+                self.mode = Mode::Search;
+                self.status = String::from("search: type to filter, enter to keep, esc to clear");
+            }
+            KeyCode::Char('t') => {
+                // cycle through the tags, then back to no filter
+                self.tag_filter = match self.tag_filter {
+                    None if !self.tags.is_empty() => Some(0),
+                    Some(i) if i + 1 < self.tags.len() => Some(i + 1),
+                    _ => None,
+                };
+                self.status = match self.tag_filter_name() {
+                    Some(name) => format!("filtering by tag: {}", name),
+                    None => String::from("tag filter cleared"),
+                };
+                self.load_notes(db).await?;
+            }
+            KeyCode::Char('T') => {
+                self.tag_filter = None;
+                self.status = String::from("tag filter cleared");
+                self.load_notes(db).await?;
+            }
+            KeyCode::Char('n') => {
+                let action = match self.focus {
+                    Focus::Notebooks => InputAction::NewNotebook,
+                    Focus::Notes => InputAction::NewNote,
+                };
+                self.start_input(action, String::new());
+            }
+            KeyCode::Char('r') => match self.focus {
+                Focus::Notebooks => {
+                    if let Some(name) = self.selected_notebook().map(|b| b.notebook_name.clone()) {
+                        self.start_input(InputAction::RenameNotebook, name);
+                    }
+                }
+                Focus::Notes => {
+                    if let Some(title) = self.selected_note().map(|n| n.note_name.clone()) {
+                        self.start_input(InputAction::RenameNote, title);
+                    }
+                }
+            },
+            KeyCode::Char('d') => {
+                let target = match self.focus {
+                    Focus::Notebooks => self.selected_notebook().map(|b| b.notebook_name.clone()),
+                    Focus::Notes => self.selected_note().map(|n| n.note_name.clone()),
+                };
+                if let Some(name) = target {
+                    self.mode = Mode::ConfirmDelete;
+                    self.status = format!("delete '{}'? (y/n)", name);
+                }
+            }
+            KeyCode::Char('g') => {
+                self.reload(db).await?;
+                self.status = String::from("reloaded");
+            }
+            KeyCode::Char('?') => {
+                self.status = String::from(
+                    "tab switch pane | j/k move | / search | t tag filter | n new | r rename | d delete | g reload | q quit",
+                );
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_search_key(&mut self, db: &DbConn, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                self.status = format!("{} matching notes", self.visible.len());
+            }
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                self.search.clear();
+                self.status = String::from("search cleared");
+                self.apply_filters(db).await?;
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+                self.apply_filters(db).await?;
+            }
+            KeyCode::Char(c) => {
+                self.search.push(c);
+                self.apply_filters(db).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_input_key(
+        &mut self,
+        db: &DbConn,
+        key: KeyEvent,
+        action: InputAction,
+    ) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                self.mode = Mode::Normal;
+                self.status = String::from("cancelled");
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Enter => {
+                self.mode = Mode::Normal;
+                let value = self.input.trim().to_string();
+                if value.is_empty() {
+                    self.status = String::from("name cannot be empty");
+                    return Ok(());
+                }
+                self.status = match self.submit_input(db, action, value).await {
+                    Ok(message) => message,
+                    Err(e) => format!("error: {}", e),
+                };
+                self.reload(db).await?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn submit_input(
+        &mut self,
+        db: &DbConn,
+        action: InputAction,
+        value: String,
+    ) -> Result<String> {
+        match action {
+            InputAction::NewNotebook => {
+                let book = create_notebook(db, value.clone(), String::new())
+                    .await
+                    .map_err(|err| names::taken(err, "notebook", &value))?;
+                // select the new notebook by id rather than assuming it comes last
+                self.notebooks = get_all_notebooks(db).await?;
+                let index =
+                    self.notebooks.iter().position(|b| b.id == book.id).map_or(0, |i| i + 1);
+                self.notebook_state.select(Some(index));
+                Ok(String::from("Successfully created notebook"))
+            }
+            InputAction::NewNote => {
+                let notebook_ids: Vec<i64> =
+                    self.selected_notebook().map(|b| b.id).into_iter().collect();
+                let txn = db.begin().await?;
+                create_note_with_links(&txn, value.clone(), String::new(), &notebook_ids, &[])
+                    .await
+                    .map_err(|err| names::taken(err, "note", &value))?;
+                txn.commit().await?;
+                Ok(String::from("Successfully created note"))
+            }
+            InputAction::RenameNotebook => {
+                let Some(book) = self.selected_notebook().cloned() else {
+                    return Ok(String::new());
+                };
+                update_notebook(db, book.id, value.clone(), book.description)
+                    .await
+                    .map_err(|err| names::taken(err, "notebook", &value))?;
+                Ok(String::from("Successfully renamed notebook"))
+            }
+            InputAction::RenameNote => {
+                let Some(note) = self.selected_note().cloned() else {
+                    return Ok(String::new());
+                };
+                update_note(db, note.id, value.clone(), note.content)
+                    .await
+                    .map_err(|err| names::taken(err, "note", &value))?;
+                Ok(String::from("Successfully renamed note"))
+            }
+        }
+    }
+
+    async fn handle_confirm_key(&mut self, db: &DbConn, key: KeyEvent) -> Result<()> {
+        self.mode = Mode::Normal;
+        if !matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y')) {
+            self.status = String::from("cancelled");
+            return Ok(());
+        }
+        let result = match self.focus {
+            Focus::Notebooks => match self.selected_notebook() {
+                Some(book) => delete_notebook_by_id(db, book.id).await,
+                None => return Ok(()),
+            },
+            Focus::Notes => match self.selected_note() {
+                Some(note) => delete_note_by_id(db, note.id).await,
+                None => return Ok(()),
+            },
+        };
+        self.status = match result {
+            Ok(message) => message,
+            Err(e) => format!("error: {}", e),
+        };
+        self.reload(db).await
+    }
+
+    fn start_input(&mut self, action: InputAction, initial: String) {
+        self.input = initial;
+        self.mode = Mode::Input(action);
+        self.status = String::from("enter to save, esc to cancel");
+    }
+
+    async fn move_selection(&mut self, db: &DbConn, delta: isize) -> Result<()> {
+        match self.focus {
+            Focus::Notebooks => {
+                let len = self.notebooks.len() + 1;
+                let next = step(self.notebook_state.selected(), delta, len);
+                if next != self.notebook_state.selected() {
+                    self.notebook_state.select(next);
+                    self.note_state.select(None);
+                    self.load_notes(db).await?;
+                }
+            }
+            Focus::Notes => {
+                let next = step(self.note_state.selected(), delta, self.visible.len());
+                if next != self.note_state.selected() {
+                    self.note_state.select(next);
+                    self.load_preview(db).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// move a list selection by `delta`, clamped to the list bounds
+fn step(current: Option<usize>, delta: isize, len: usize) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let current = current.unwrap_or(0) as isize;
+    Some((current + delta).clamp(0, len as isize - 1) as usize)
+}
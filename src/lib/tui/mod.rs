@@ -0,0 +1,33 @@
+// interactive terminal browser for notebooks, notes and tags
+mod app;
+mod ui;
+
+use anyhow::Result;
+use app::App;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use sea_orm::DbConn;
+use std::time::Duration;
+
+pub async fn run(db: &DbConn) -> Result<()> {
+    let mut app = App::new(db).await?;
+    let mut terminal = ratatui::init();
+
+    let result = async {
+        while app.running {
+            terminal.draw(|frame| ui::draw(frame, &mut app))?;
+            if event::poll(Duration::from_millis(250))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        app.handle_key(db, key).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    // always restore the terminal, even when the loop failed
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,96 @@
+// rendering of the three tui panes and the status line
+use super::app::{App, Focus, InputAction, Mode};
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, status] =
+        Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+    let [notebooks_area, notes_area, preview_area] = Layout::horizontal([
+        Constraint::Percentage(20),
+        Constraint::Percentage(30),
+        Constraint::Percentage(50),
+    ])
+    .areas(main);
+
+    // notebooks pane
+    let mut items = vec![ListItem::new("All notes")];
+    items.extend(app.notebooks.iter().map(|b| ListItem::new(b.notebook_name.as_str())));
+    let notebooks = List::new(items)
+        .block(pane_block("Notebooks", app.focus == Focus::Notebooks))
+        .highlight_style(highlight());
+    frame.render_stateful_widget(notebooks, notebooks_area, &mut app.notebook_state);
+
+    // notes pane
+    let mut title = format!("Notes ({})", app.visible.len());
+    if let Some(tag) = app.tag_filter_name() {
+        title.push_str(&format!(" #{}", tag));
+    }
+    if !app.search.is_empty() {
+        title.push_str(&format!(" /{}", app.search));
+    }
+    let items: Vec<ListItem> =
+        app.visible.iter().map(|&i| ListItem::new(app.notes[i].note_name.as_str())).collect();
+    let notes = List::new(items)
+        .block(pane_block(&title, app.focus == Focus::Notes))
+        .highlight_style(highlight());
+    frame.render_stateful_widget(notes, notes_area, &mut app.note_state);
+
+    // preview pane
+    let preview = match app.selected_note() {
+        Some(note) => {
+            let mut lines = vec![
+                Line::from(Span::styled(
+                    note.note_name.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )),
+                Line::from(Span::styled(
+                    app.preview_tags
+                        .iter()
+                        .map(|t| format!("#{}", t))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    Style::default().fg(Color::Cyan),
+                )),
+                Line::default(),
+            ];
+            lines.extend(note.content.lines().map(|l| Line::from(l.to_string())));
+            Paragraph::new(lines)
+        }
+        None => Paragraph::new("No note selected"),
+    };
+    frame.render_widget(
+        preview.block(pane_block("Preview", false)).wrap(Wrap { trim: false }),
+        preview_area,
+    );
+
+    // status line, doubles as the input prompt
+    let status_line = match &app.mode {
+        Mode::Search => format!("/{}", app.search),
+        Mode::Input(action) => {
+            let prompt = match action {
+                InputAction::NewNotebook => "new notebook",
+                InputAction::NewNote => "new note",
+                InputAction::RenameNotebook => "rename notebook",
+                InputAction::RenameNote => "rename note",
+            };
+            format!("{}: {}", prompt, app.input)
+        }
+        Mode::Normal | Mode::ConfirmDelete => app.status.clone(),
+    };
+    frame.render_widget(Paragraph::new(status_line), status);
+}
+
+fn pane_block(title: &str, focused: bool) -> Block<'_> {
+    let style = if focused { Style::default().fg(Color::Yellow) } else { Style::default() };
+    Block::default().borders(Borders::ALL).border_style(style).title(title)
+}
+
+fn highlight() -> Style {
+    Style::default().add_modifier(Modifier::REVERSED)
+}
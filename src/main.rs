@@ -1,10 +1,37 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{anyhow, Result};
+use chrono::{Local, NaiveDate, Utc};
+use clap::{ArgAction, ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::env::{self, EnvCompleter};
+use clap_complete::{ArgValueCandidates, CompleteEnv};
+use clap_markdown::MarkdownOptions;
+use migration::{Migrator, MigratorTrait};
 use modnote::{
-    crud::notebook::*,
+    complete,
+    config::{self, Color, Config, Output},
+    crud::attachment::*,
+    crud::merge::*,
     crud::note::*,
-    db::set_db_options};
-use sea_orm::Database;
+    crud::notebook::*,
+    crud::query::*,
+    crud::split::{split_note, split_plan},
+    crud::status::*,
+    crud::tag::*,
+    crud::{ListOptions, Page, SortKey},
+    db::set_db_options,
+    doctor, editor,
+    entities::{notebooks, notes, tags},
+    input, journal,
+    logging::{self, LogOptions},
+    markdown::TerminalRenderer,
+    names, pager, progress, publish, query, resolve, server, snippets, stats, templates, tui,
+};
+use sea_orm::{Database, TransactionTrait};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 // A template for Rust CLI applications
 #[derive(Parser, Debug)]
@@ -14,163 +41,1232 @@ struct Cli {
     // Subcommand to run
     #[command(subcommand)]
     command: Option<Commands>,
+
+    /// database file, overriding the config
+    #[arg(long, global = true, help = "Path of the database")]
+    db: Option<PathBuf>,
+    /// output format, overriding the config
+    #[arg(long, global = true, help = "Output format")]
+    output: Option<Output>,
+    /// when to render with color, overriding the config
+    #[arg(long, global = true, help = "When to use color")]
+    color: Option<Color>,
+
+    /// log more, repeat for more detail
+    #[arg(short, long, global = true, action = ArgAction::Count, help = "Log more (-v, -vv, -vvv)")]
+    verbose: u8,
+    /// log less, repeat to silence errors too
+    #[arg(short, long, global = true, action = ArgAction::Count, help = "Log less (-q, -qq)")]
+    quiet: u8,
+    /// also write logs as json lines to this file
+    #[arg(long, global = true, help = "Json log file")]
+    log_file: Option<PathBuf>,
+    /// log every sql statement with its run time
+    #[arg(long, global = true, help = "Log sql statements")]
+    log_sql: bool,
+    /// statements slower than this are logged as warnings
+    #[arg(long, global = true, default_value_t = 500, help = "Slow query threshold in ms")]
+    slow_query_ms: u64,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Create a new notebook, note or tag
-    New {
+    /// Add, list, show, edit, merge or remove notebooks
+    Notebook {
         #[command(subcommand)]
-        subcommands: Option<Subcommands>,
+        action: NotebookCommand,
     },
-    /// Get notebook(s), note(s), or tag(s)
-    Get {
+    /// Add, list, show, edit, merge, split, attach files to or remove notes
+    Note {
+        #[command(subcommand)]
+        action: NoteCommand,
+    },
+    /// Add, list, show, rename, merge or remove tags
+    Tag {
+        #[command(subcommand)]
+        action: TagCommand,
+    },
+    /// List, show, edit or remove note templates
+    Template {
         #[command(subcommand)]
-        subcommands: Option<Subcommands>,
+        action: TemplateCommand,
+    },
+    /// Browse notebooks, notes and tags in an interactive terminal UI
+    Tui,
+    /// Serve notebooks, notes and tags over a local HTTP JSON API
+    Serve {
+        /// address to listen on
+        #[arg(short, long, default_value = "127.0.0.1:7878", help = "Address to listen on")]
+        bind: SocketAddr,
+    },
+    /// Render every notebook and note into a read-only static HTML site
+    Publish {
+        /// directory to write the site into
+        #[arg(short, long, default_value = "site", help = "Output directory")]
+        out: PathBuf,
+    },
+    /// Compile-check the fenced rust code blocks in notes
+    CheckSnippets {
+        /// only check notes in this notebook
+        #[arg(short, long, help = "Name of notebook", add = ArgValueCandidates::new(complete::notebooks))]
+        notebook: Option<String>,
+        /// seconds each snippet may run before it fails
+        #[arg(long, default_value_t = 10, help = "Run timeout in seconds")]
+        timeout: u64,
+    },
+    /// Build and run a fenced rust snippet from a note
+    Run {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// which rust block of the note to run, counting from 1
+        #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), help = "Block number")]
+        block: u64,
+        /// seconds the snippet may run before it is killed
+        #[arg(long, default_value_t = 10, help = "Run timeout in seconds")]
+        timeout: u64,
+        /// save the captured output into the note as an output block
+        #[arg(short, long, help = "Save output into the note")]
+        save: bool,
+    },
+    /// Find notes matching a filter, e.g. 'tag:iterators AND NOT tag:done AND updated>2026-01-01'
+    Find {
+        /// the filter, or @name to run a saved one
+        #[arg(required_unless_present_any = ["saved", "forget"])]
+        query: Option<String>,
+        /// save the filter under this name
+        #[arg(long, help = "Name to save the query under")]
+        save: Option<String>,
+        /// list the saved queries
+        #[arg(long, conflicts_with_all = ["query", "save", "forget"], help = "List saved queries")]
+        saved: bool,
+        /// delete the saved query of this name
+        #[arg(long, conflicts_with_all = ["query", "save"], help = "Name of saved query to delete")]
+        forget: Option<String>,
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Open today's journal note, listing the notes created or updated today
+    Today {
+        /// print the note instead of opening it in the editor
+        #[arg(long, help = "Print the note without editing it")]
+        no_edit: bool,
+    },
+    /// Summarize the notes studied on a day or across a week
+    Journal {
+        /// summarize every day from monday up to the date
+        #[arg(short, long, help = "Summarize the week")]
+        week: bool,
+        /// day to summarize as YYYY-MM-DD, today when not given
+        #[arg(long, help = "Date to summarize")]
+        date: Option<NaiveDate>,
+    },
+    /// Show study progress per notebook with an activity calendar
+    Progress {
+        /// only show the notes of this notebook
+        #[arg(short, long, help = "Name of notebook", add = ArgValueCandidates::new(complete::notebooks))]
+        notebook: Option<String>,
+        /// number of weeks the activity calendar covers
+        #[arg(long, default_value_t = 12, help = "Weeks of activity to show")]
+        weeks: u64,
+    },
+    /// Show counts and activity across notebooks, notes and tags
+    Stats {
+        /// how many days back recent activity covers
+        #[arg(long, default_value_t = 7, help = "Days of recent activity")]
+        days: u64,
+    },
+    /// Print a shell completion script, e.g. `source <(modnote completions bash)`
+    Completions {
+        /// shell to complete in
+        shell: Shell,
+    },
+    /// Write man pages and a markdown command reference generated from this cli
+    GenerateMan {
+        /// directory to write the roff man pages into
+        #[arg(short, long, default_value = "man", help = "Man page directory")]
+        out: PathBuf,
+        /// file to write the markdown command reference to
+        #[arg(long, default_value = "docs/cli.md", help = "Markdown reference file")]
+        markdown: PathBuf,
     },
-    /// Update notebook, note, or tag
-    Update {
+    /// Show or change settings in the config file
+    Config {
         #[command(subcommand)]
-        subcommands: Option<Subcommands>,
+        action: ConfigAction,
     },
-    /// Delete notebook, note or tag
-    Delete {
+    /// Check the database for problems: foreign keys, integrity, orphan rows,
+    /// empty names and duplicates
+    #[command(args_conflicts_with_subcommands = true)]
+    Doctor {
+        /// fix the problems found
+        #[arg(long, help = "Repair the problems found")]
+        repair: bool,
         #[command(subcommand)]
-        subcommands: Option<Subcommands>,
-        // #[arg(short, long, help = "Get all notebooks, notes, or tags")]
-        // all: Option<bool>,
+        check: Option<DoctorCheck>,
     },
 }
 
+// paging and ordering of `list` listings
+#[derive(Args, Debug)]
+struct ListArgs {
+    /// number of records per page
+    #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u64).range(1..), help = "Records per page")]
+    limit: u64,
+    /// number of records to skip
+    #[arg(long, default_value_t = 0, help = "Records to skip")]
+    offset: u64,
+    /// field to order by: id or name
+    #[arg(long, default_value = "id", help = "Sort by id or name")]
+    sort: SortKey,
+    /// order from last to first
+    #[arg(long, help = "Sort descending")]
+    reverse: bool,
+}
+
+impl ListArgs {
+    fn options(&self) -> ListOptions {
+        ListOptions {
+            limit: self.limit,
+            offset: self.offset,
+            sort: self.sort,
+            descending: self.reverse,
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
-enum Subcommands {
-    /// Create a new notebook
-    Notebook {
+enum NotebookCommand {
+    /// Create a notebook
+    Add {
         /// name of the notebook
-        #[arg(short, long, help = "Name of notebook")]
-        name: Option<String>,
+        name: String,
         /// description of the notebook
         #[arg(short, long, help = "Description of notebook")]
+        desc: String,
+        /// make a smart notebook holding the notes that match this filter
+        #[arg(long, help = "Filter of a smart notebook, e.g. 'tag:unsafe AND updated<30d'")]
+        filter: Option<String>,
+    },
+    /// List notebooks a page at a time
+    List {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Show a notebook and its notes
+    Show {
+        /// name of the notebook
+        #[arg(add = ArgValueCandidates::new(complete::notebooks))]
+        name: String,
+    },
+    /// Rename a notebook or change its description
+    #[command(group(ArgGroup::new("change").required(true).multiple(true)))]
+    Edit {
+        /// name of the notebook
+        #[arg(add = ArgValueCandidates::new(complete::notebooks))]
+        name: String,
+        /// new name of the notebook
+        #[arg(long, group = "change", help = "New name of notebook")]
+        rename: Option<String>,
+        /// new description of the notebook
+        #[arg(short, long, group = "change", help = "Description of notebook")]
         desc: Option<String>,
     },
-    /// Create a new note
-    Note {
-        /// title of the note
-        #[arg(short, long, help = "Title of note")]
-        title: Option<String>,
+    /// Merge notebook B into notebook A, moving its notes
+    Merge {
+        /// name of the notebook to keep
+        #[arg(add = ArgValueCandidates::new(complete::notebooks))]
+        keep: String,
+        /// name of the notebook merged into it and deleted
+        #[arg(add = ArgValueCandidates::new(complete::notebooks))]
+        other: String,
+    },
+    /// Delete a notebook, keeping its notes
+    Rm {
+        /// name of the notebook
+        #[arg(required_unless_present = "all", add = ArgValueCandidates::new(complete::notebooks))]
+        name: Option<String>,
+        /// delete every notebook
+        #[arg(long, conflicts_with = "name", help = "Delete all notebooks")]
+        all: bool,
+    },
+}
 
-        /// content of the note
-        #[arg(short, long, help = "Content of note")]
+#[derive(Subcommand, Debug)]
+enum NoteCommand {
+    /// Create a note, optionally filed in a notebook and tagged
+    #[command(group(ArgGroup::new("source").required(true)))]
+    Add {
+        /// title of the note
+        title: String,
+        /// content of the note, `-` reads it from stdin
+        #[arg(short, long, group = "source", help = "Content of note, - for stdin")]
         content: Option<String>,
+        /// file to read the content from, `-` for stdin
+        #[arg(long, group = "source", help = "File with the content of note")]
+        content_file: Option<PathBuf>,
+        /// template to fill in and open in the editor
+        #[arg(long, group = "source", help = "Name of template", add = ArgValueCandidates::new(complete::templates))]
+        template: Option<String>,
+        /// path of the std item for the template's {{path}}, defaults to the title
+        #[arg(long, requires = "template", help = "Item path, e.g. std::vec::Vec")]
+        path: Option<String>,
+        /// notebook to file the note in, defaults to default_notebook from the config
+        #[arg(long, help = "Name of notebook", add = ArgValueCandidates::new(complete::notebooks))]
+        notebook: Option<String>,
+        /// tags for the note, created when missing
+        #[arg(long = "tag", help = "Tag for note, may be repeated", add = ArgValueCandidates::new(complete::tags))]
+        tags: Vec<String>,
     },
-    /// Create a new tag
-    Tag {
-        /// name of the tag
-        #[arg(short, long, help = "Name of tag")]
+    /// List notes a page at a time
+    List {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Show a note with its content rendered as terminal markdown
+    Show {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// print the stored markdown without rendering or paging
+        #[arg(long, help = "Print raw markdown")]
+        raw: bool,
+    },
+    /// Change a note's content, in the configured editor unless --content or --content-file is given
+    Edit {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// new content of the note, `-` reads it from stdin
+        #[arg(short, long, help = "Content of note, - for stdin")]
+        content: Option<String>,
+        /// file to read the new content from, `-` for stdin
+        #[arg(long, conflicts_with = "content", help = "File with the content of note")]
+        content_file: Option<PathBuf>,
+        /// new title of the note
+        #[arg(long, help = "New title of note")]
+        rename: Option<String>,
+    },
+    /// Merge note B into note A, appending its content and moving its tags and notebooks
+    Merge {
+        /// title of the note to keep
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        keep: String,
+        /// title of the note merged into it and deleted
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        other: String,
+    },
+    /// Split a note into one note per markdown heading
+    Split {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// split at headings
+        #[arg(long, required = true, help = "Split at markdown headings")]
+        at_heading: bool,
+        /// heading level to split at, defaults to the top-most level in the note
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=6), help = "Heading level")]
+        level: Option<u8>,
+    },
+    /// Attach a file, such as a diagram or saved output, to a note
+    Attach {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// file to attach, `-` reads it from stdin
+        file: PathBuf,
+        /// name to store the attachment under, defaults to the file's name
+        #[arg(long, help = "Name of attachment")]
+        name: Option<String>,
+    },
+    /// List the files attached to a note
+    Attachments {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+    },
+    /// Write the files attached to a note into a directory
+    Extract {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// attachment to extract, all of them when left out
         name: Option<String>,
+        /// directory to write the files into
+        #[arg(short, long, default_value = ".", help = "Output directory")]
+        out: PathBuf,
+        /// replace files that already exist
+        #[arg(long, help = "Overwrite existing files")]
+        force: bool,
+    },
+    /// Mark how far a note has been studied, or show its status history
+    Status {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+        /// new status, the history is shown when left out
+        status: Option<Status>,
+    },
+    /// Delete a note
+    Rm {
+        /// title of the note
+        #[arg(add = ArgValueCandidates::new(complete::notes))]
+        title: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TagCommand {
+    /// Create a tag
+    Add {
+        /// name of the tag
+        name: String,
+    },
+    /// List tags a page at a time
+    List {
+        #[command(flatten)]
+        list: ListArgs,
+    },
+    /// Show a tag and the notes tagged with it
+    Show {
+        /// name of the tag
+        #[arg(add = ArgValueCandidates::new(complete::tags))]
+        name: String,
+    },
+    /// Rename a tag
+    Rename {
+        /// name of the tag
+        #[arg(add = ArgValueCandidates::new(complete::tags))]
+        name: String,
+        /// new name of the tag
+        new_name: String,
+    },
+    /// Merge tag B into tag A, retagging its notes
+    Merge {
+        /// name of the tag to keep
+        #[arg(add = ArgValueCandidates::new(complete::tags))]
+        keep: String,
+        /// name of the tag merged into it and deleted
+        #[arg(add = ArgValueCandidates::new(complete::tags))]
+        other: String,
+    },
+    /// Delete a tag, removing it from its notes
+    Rm {
+        /// name of the tag
+        #[arg(add = ArgValueCandidates::new(complete::tags))]
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TemplateCommand {
+    /// List the built-in and user templates
+    List,
+    /// Print a template
+    Show {
+        /// name of the template
+        #[arg(add = ArgValueCandidates::new(complete::templates))]
+        name: String,
+    },
+    /// Create or change a template in the editor, a built-in is copied first
+    Edit {
+        /// name of the template
+        #[arg(add = ArgValueCandidates::new(complete::templates))]
+        name: String,
+    },
+    /// Delete a user template
+    Rm {
+        /// name of the template
+        #[arg(add = ArgValueCandidates::new(complete::templates))]
+        name: String,
+    },
+}
+
+// shells a completion script can be printed for; notebook, note and tag names
+// are completed by calling back into modnote
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print the value of a setting
+    Get {
+        /// setting such as db, default_notebook or aliases.<name>
+        key: String,
+    },
+    /// Change a setting, an empty value removes it
+    Set {
+        /// setting such as db, default_notebook or aliases.<name>
+        key: String,
+        /// new value of the setting
+        value: String,
+    },
+    /// Print every setting
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+enum DoctorCheck {
+    /// Find notebooks, notes and tags whose names differ only in case or spacing
+    Duplicates {
+        /// merge each duplicate into the oldest record with the same name
+        #[arg(long, help = "Merge the duplicates")]
+        merge: bool,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // answer the shell when it asks for completions
+    CompleteEnv::with_factory(Cli::command).complete();
+
+    // load the settings and expand a user-defined alias before parsing
+    let config = Config::load()?;
+    let mut args: Vec<String> = std::env::args().collect();
+    config.expand_alias(&mut args, |name| Cli::command().find_subcommand(name).is_some());
+    let cli = Cli::parse_from(args);
+
+    // initialize logging (kept off the terminal for the tui, which owns it)
+    logging::init(&LogOptions {
+        verbosity: cli.verbose.min(3) as i8 - cli.quiet.min(3) as i8,
+        sql: cli.log_sql,
+        file: cli.log_file.as_deref(),
+        quiet_terminal: matches!(cli.command, Some(Commands::Tui)),
+    })?;
+
+    // completion scripts and settings don't touch the database
+    if let Some(Commands::Completions { shell }) = &cli.command {
+        let completer: &dyn EnvCompleter = match shell {
+            Shell::Bash => &env::Bash,
+            Shell::Zsh => &env::Zsh,
+            Shell::Fish => &env::Fish,
+        };
+        let exe = std::env::current_exe()?;
+        let exe = exe.to_string_lossy();
+        completer.write_registration("COMPLETE", "modnote", "modnote", &exe, &mut io::stdout())?;
+        return Ok(());
+    }
+    if let Some(Commands::GenerateMan { out, markdown }) = &cli.command {
+        fs::create_dir_all(out)?;
+        clap_mangen::generate_to(Cli::command(), out)?;
+        let options = MarkdownOptions::new()
+            .title(String::from("modnote command reference"))
+            .show_footer(false);
+        if let Some(dir) = markdown.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(markdown, clap_markdown::help_markdown_custom::<Cli>(&options))?;
+        println!(
+            "Successfully generated man pages in {} and {}",
+            out.display(),
+            markdown.display()
+        );
+        return Ok(());
+    }
+    if let Some(Commands::Config { action }) = &cli.command {
+        match action {
+            ConfigAction::Get { key } => println!("{}", config.get(key)?),
+            ConfigAction::Set { key, value } => {
+                let path = config::set(key, value)?;
+                println!("Successfully set {} in {}", key, path.display());
+            }
+            ConfigAction::List => {
+                for (key, value) in config.list() {
+                    println!("{} = {}", key, value)
+                }
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Commands::Template { action }) = &cli.command {
+        match action {
+            TemplateCommand::List => {
+                for template in templates::list()? {
+                    match &template.file {
+                        Some(file) => println!("{}  {}", template.name, file.display()),
+                        None => println!("{}  (built-in)", template.name),
+                    }
+                }
+            }
+            TemplateCommand::Show { name } => print!("{}", templates::get(name)?.text),
+            TemplateCommand::Edit { name } => {
+                let text = match templates::get(name) {
+                    Ok(template) => template.text,
+                    Err(_) => String::new(),
+                };
+                let path = templates::save(name, &editor::edit(&text, config.editor.as_deref())?)?;
+                println!("Successfully saved template {} to {}", name, path.display());
+            }
+            TemplateCommand::Rm { name } => {
+                let path = templates::remove(name)?;
+                println!("Successfully deleted template {} from {}", name, path.display());
+            }
+        }
+        return Ok(());
+    }
+    let output = cli.output.unwrap_or(config.output);
+    let color = match cli.color.unwrap_or(config.color) {
+        Color::Auto => std::io::stdout().is_terminal(),
+        Color::Always => true,
+        Color::Never => false,
+    };
+
     // connect to the database
-    let db_options = set_db_options();
+    let db_path = cli.db.clone().or(config.db.clone()).unwrap_or(config::DEFAULT_DB.into());
+    let slow = Duration::from_millis(cli.slow_query_ms);
+    let db_options = set_db_options(&db_path, cli.log_sql, slow);
     let db = &Database::connect(db_options).await?;
 
-    // initialize tracing
-    tracing_subscriber::fmt().with_max_level(tracing::Level::DEBUG).with_test_writer().init();
-
-    let cli = Cli::parse();
+    // bring the schema up to date; duplicate names left from before they were
+    // unique are only merged when asked to
+    if let Some(Commands::Doctor { check: Some(DoctorCheck::Duplicates { merge: true }), .. }) =
+        &cli.command
+    {
+        migration::MERGE_DUPLICATES.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+    Migrator::up(db, None).await?;
 
     match &cli.command {
-        // Parse "New" Command
-        Some(Commands::New { subcommands }) => match subcommands.as_ref().unwrap() {
-            Subcommands::Notebook { name, desc } => {
-                if name.is_none() || desc.is_none() {
-                    println!("\nerror: name and description required to create new notebook. use --help for correct usage\n")
-                } else {
-                    let name = name.to_owned().unwrap();
-                    let desc = desc.to_owned().unwrap();
-                    create_notebook(db, name, desc).await?;
+        // Manage notebooks
+        Some(Commands::Notebook { action }) => match action {
+            NotebookCommand::Add { name, desc, filter } => match filter {
+                Some(filter) => {
+                    // point at a syntax error before storing the filter
+                    if !filter.trim().starts_with('@') {
+                        query::parse(filter)
+                            .map_err(|err| anyhow!("{}\n{}", err, err.pointer(filter)))?;
+                    }
+                    create_smart_notebook(db, name.to_owned(), desc.to_owned(), filter.to_owned())
+                        .await
+                        .map_err(|err| names::taken(err, "notebook", name))?;
+                    println!("Successfully created smart notebook");
+                }
+                None => {
+                    create_notebook(db, name.to_owned(), desc.to_owned())
+                        .await
+                        .map_err(|err| names::taken(err, "notebook", name))?;
                     println!("Successfully created notebook");
                 }
+            },
+            NotebookCommand::List { list } => {
+                let page = list_notebooks(db, &list.options()).await?;
+                print_page(&page, list, output)?;
             }
-            Subcommands::Note { title, content } => {
-                if title.is_none() || content.is_none() {
-                    println!("\nerror: title and content required to create new note. use --help for correct usage\n")
+            NotebookCommand::Show { name } => {
+                let notebook = resolve::notebook(db, name).await?;
+                // a smart notebook's notes are found only now
+                let notes = get_notes_in_notebook(db, notebook.id).await?;
+                if output == Output::Json {
+                    let record = serde_json::json!({ "notebook": notebook, "notes": notes });
+                    println!("{}", serde_json::to_string_pretty(&record)?);
                 } else {
-                    let title = title.to_owned().unwrap();
-                    let content = content.to_owned().unwrap();
-                    create_note(db, title, content).await?;
-                    println!("Successfully created note");
+                    println!("{}", notebook.line());
+                    print_titles(&notes);
                 }
             }
-            Subcommands::Tag { name: _ } => {
-                println!("Tag: ");
-                todo!("Implement create tag functionality")
+            NotebookCommand::Edit { name, rename, desc } => {
+                let notebook = resolve::notebook_exact(db, name).await?;
+                let name = rename.clone().unwrap_or(notebook.notebook_name);
+                let desc = desc.clone().unwrap_or(notebook.description);
+                update_notebook(db, notebook.id, name.clone(), desc)
+                    .await
+                    .map_err(|err| names::taken(err, "notebook", &name))?;
+                println!("Successfully updated notebook");
+            }
+            NotebookCommand::Merge { keep, other } => {
+                let (keep, other) = (
+                    resolve::notebook_exact(db, keep).await?,
+                    resolve::notebook_exact(db, other).await?,
+                );
+                if keep.id == other.id {
+                    anyhow::bail!("cannot merge notebook '{}' into itself", keep.notebook_name);
+                }
+                let txn = db.begin().await?;
+                merge_notebooks(&txn, keep.id, other.id).await?;
+                txn.commit().await?;
+                println!(
+                    "Successfully merged notebook '{}' into '{}'",
+                    other.notebook_name, keep.notebook_name
+                );
+            }
+            NotebookCommand::Rm { name: Some(name), .. } => {
+                let notebook = resolve::notebook_exact(db, name).await?;
+                delete_notebook_by_id(db, notebook.id).await?;
+                println!("Successfully deleted notebook with name: {}", notebook.notebook_name);
+            }
+            NotebookCommand::Rm { name: None, .. } => {
+                println!("{}", delete_all_notebooks(db).await?);
             }
         },
 
-        // Parse "Get" Command
-        Some(Commands::Get { subcommands }) => match subcommands.as_ref().unwrap() {
-            Subcommands::Notebook { name, desc: _ } => {
-                if name.is_none() {
-                    let notebooks = get_all_notebooks(db).await?;
-                    for notebook in notebooks {
-                        println!("{:?}", notebook)
+        // Manage notes
+        Some(Commands::Note { action }) => match action {
+            NoteCommand::Add { title, content, content_file, template, path, notebook, tags } => {
+                let notebook = notebook.as_ref().or(config.default_notebook.as_ref());
+                let content = match template {
+                    // pre-fill the template and let the user write the rest
+                    Some(name) => {
+                        let vars = templates::Vars {
+                            title,
+                            path: path.as_deref().unwrap_or(title),
+                            date: Local::now().date_naive(),
+                            notebook: notebook.map(String::as_str),
+                        };
+                        let text = templates::render(&templates::get(name)?.text, &vars);
+                        editor::edit(&text, config.editor.as_deref())?
+                    }
+                    None => {
+                        let limit = config.max_content_bytes();
+                        read_content(content, content_file, limit)?.unwrap_or_default()
                     }
+                };
+                let notebook_ids = match notebook {
+                    Some(name) => vec![resolve::notebook_exact(db, name).await?.id],
+                    None => Vec::new(),
+                };
+                // create, file and tag the note atomically
+                let txn = db.begin().await?;
+                create_note_with_links(&txn, title.to_owned(), content, &notebook_ids, tags)
+                    .await
+                    .map_err(|err| names::taken(err, "note", title))?;
+                txn.commit().await?;
+                println!("Successfully created note");
+            }
+            NoteCommand::List { list } => {
+                let page = list_notes(db, &list.options()).await?;
+                print_page(&page, list, output)?;
+            }
+            NoteCommand::Show { title, raw } => {
+                let note = resolve::note(db, title).await?;
+                if output == Output::Json {
+                    println!("{}", serde_json::to_string_pretty(&note)?);
+                } else if *raw || !color {
+                    println!("{}", note.content);
                 } else {
-                    let notebook =
-                        get_notebook_by_name(db, name.to_owned().unwrap_or_default()).await?;
-                    println!("{:?}", notebook)
+                    let markdown = format!("# {}\n\n{}", note.note_name, note.content);
+                    pager::page(
+                        &TerminalRenderer::new().render(&markdown),
+                        config.pager.as_deref(),
+                    )?;
+                }
+            }
+            NoteCommand::Edit { title, content, content_file, rename } => {
+                let content = read_content(content, content_file, config.max_content_bytes())?;
+                let note = resolve::note_exact(db, title).await?;
+                let content = match content {
+                    Some(content) => content,
+                    // a rename alone leaves the content as it is
+                    None if rename.is_some() => note.content,
+                    None => editor::edit(&note.content, config.editor.as_deref())?,
+                };
+                let title = rename.clone().unwrap_or(note.note_name);
+                update_note(db, note.id, title.clone(), content)
+                    .await
+                    .map_err(|err| names::taken(err, "note", &title))?;
+                println!("Successfully updated note");
+            }
+            NoteCommand::Merge { keep, other } => {
+                let (keep, other) =
+                    (resolve::note_exact(db, keep).await?, resolve::note_exact(db, other).await?);
+                if keep.id == other.id {
+                    anyhow::bail!("cannot merge note '{}' into itself", keep.note_name);
+                }
+                let txn = db.begin().await?;
+                merge_notes(&txn, keep.id, other.id).await?;
+                txn.commit().await?;
+                println!(
+                    "Successfully merged note '{}' into '{}'",
+                    other.note_name, keep.note_name
+                );
+            }
+            NoteCommand::Split { title, at_heading: _, level } => {
+                let note = resolve::note_exact(db, title).await?;
+                // fail on taken titles before anything is written
+                split_plan(db, &note, *level).await?;
+                let txn = db.begin().await?;
+                let created = split_note(&txn, note.id, *level).await?;
+                txn.commit().await?;
+                println!(
+                    "Successfully split note '{}' into {} notes:",
+                    note.note_name,
+                    created.len()
+                );
+                for new_note in created {
+                    println!("  {}", new_note.note_name);
+                }
+            }
+            NoteCommand::Attach { title, file, name } => {
+                let file_name = match (name, file.file_name()) {
+                    (Some(name), _) => name.to_owned(),
+                    (None, Some(name)) if file != Path::new("-") => {
+                        name.to_string_lossy().into_owned()
+                    }
+                    _ => anyhow::bail!("give the attachment a --name when attaching stdin"),
+                };
+                let data = input::read_attachment(file, config.max_attachment_bytes())?;
+                let note = resolve::note_exact(db, title).await?;
+                let attachment = add_attachment(db, note.id, file_name, data).await?;
+                println!(
+                    "Successfully attached {} ({}, {} bytes) to note {}",
+                    attachment.file_name, attachment.mime_type, attachment.size, note.note_name
+                );
+            }
+            NoteCommand::Attachments { title } => {
+                let note = resolve::note(db, title).await?;
+                let attachments = get_attachments_for_note(db, note.id).await?;
+                match output {
+                    Output::Json => println!("{}", serde_json::to_string_pretty(&attachments)?),
+                    Output::Text if attachments.is_empty() => {
+                        println!("Note {} has no attachments", note.note_name)
+                    }
+                    Output::Text => {
+                        for attachment in attachments {
+                            println!(
+                                "{}  {}  {} bytes  sha256:{}",
+                                attachment.file_name,
+                                attachment.mime_type,
+                                attachment.size,
+                                attachment.hash
+                            );
+                        }
+                    }
+                }
+            }
+            NoteCommand::Status { title, status: Some(status) } => {
+                let note = resolve::note_exact(db, title).await?;
+                let txn = db.begin().await?;
+                let change = set_status(&txn, note.id, *status).await?;
+                txn.commit().await?;
+                match change {
+                    Some(change) => println!(
+                        "Successfully marked note '{}' as {} (was {})",
+                        note.note_name, change.to_status, change.from_status
+                    ),
+                    None => println!("Note '{}' is already {}", note.note_name, status),
+                }
+            }
+            NoteCommand::Status { title, status: None } => {
+                let note = resolve::note(db, title).await?;
+                let history = get_status_history(db, note.id).await?;
+                match output {
+                    Output::Json => println!("{}", serde_json::to_string_pretty(&history)?),
+                    Output::Text => {
+                        println!("{} is {}", note.note_name, note.status);
+                        for change in history {
+                            println!(
+                                "  {}  {} -> {}",
+                                change.changed_at.format("%Y-%m-%d %H:%M"),
+                                change.from_status,
+                                change.to_status
+                            );
+                        }
+                    }
                 }
             }
-            Subcommands::Note { title: _, content: _ } => {
-                println!("Note: ");
-                todo!("Implement get note functionality")
+            NoteCommand::Extract { title, name, out, force } => {
+                let note = resolve::note(db, title).await?;
+                let attachments = match name {
+                    Some(name) => vec![get_attachment(db, note.id, name).await?],
+                    None => get_attachments_for_note(db, note.id).await?,
+                };
+                if attachments.is_empty() {
+                    println!("Note {} has no attachments", note.note_name);
+                }
+                fs::create_dir_all(out)?;
+                for attachment in attachments {
+                    let path = out.join(&attachment.file_name);
+                    if path.exists() && !force {
+                        anyhow::bail!(
+                            "{} already exists, use --force to replace it",
+                            path.display()
+                        );
+                    }
+                    fs::write(&path, get_attachment_data(db, attachment.id).await?)?;
+                    println!("Successfully extracted {}", path.display());
+                }
             }
-            Subcommands::Tag { name: _ } => {
-                println!("Tag: ");
-                todo!("Implement get tag functionality")
+            NoteCommand::Rm { title } => {
+                let note = resolve::note_exact(db, title).await?;
+                delete_note_by_id(db, note.id).await?;
+                println!("Successfully deleted note with title: {}", note.note_name);
             }
         },
 
-        // Parse "Up" Command
-        Some(Commands::Update { subcommands }) => match subcommands.as_ref().unwrap() {
-            Subcommands::Notebook { name, desc: _ } => {
-                update_notebook_by_name(db, name.to_owned()).await?;
+        // Manage tags
+        Some(Commands::Tag { action }) => match action {
+            TagCommand::Add { name } => {
+                create_tag(db, name.to_owned())
+                    .await
+                    .map_err(|err| names::taken(err, "tag", name))?;
+                println!("Successfully created tag");
+            }
+            TagCommand::List { list } => {
+                let page = list_tags(db, &list.options()).await?;
+                print_page(&page, list, output)?;
+            }
+            TagCommand::Show { name } => {
+                let tag = resolve::tag(db, name).await?;
+                let notes = get_notes_with_tag(db, tag.id).await?;
+                if output == Output::Json {
+                    let record = serde_json::json!({ "tag": tag, "notes": notes });
+                    println!("{}", serde_json::to_string_pretty(&record)?);
+                } else {
+                    println!("{}", tag.line());
+                    print_titles(&notes);
+                }
+            }
+            TagCommand::Rename { name, new_name } => {
+                let tag = resolve::tag_exact(db, name).await?;
+                rename_tag(db, tag.id, new_name.to_owned())
+                    .await
+                    .map_err(|err| names::taken(err, "tag", new_name))?;
+                println!("Successfully renamed tag '{}' to '{}'", tag.tag_name, new_name);
             }
-            Subcommands::Note { title: _, content: _ } => {
-                println!("Note: ")
+            TagCommand::Merge { keep, other } => {
+                let (keep, other) =
+                    (resolve::tag_exact(db, keep).await?, resolve::tag_exact(db, other).await?);
+                if keep.id == other.id {
+                    anyhow::bail!("cannot merge tag '{}' into itself", keep.tag_name);
+                }
+                let txn = db.begin().await?;
+                merge_tags(&txn, keep.id, other.id).await?;
+                txn.commit().await?;
+                println!("Successfully merged tag '{}' into '{}'", other.tag_name, keep.tag_name);
             }
-            Subcommands::Tag { name: _ } => {
-                println!("Tag: ")
+            TagCommand::Rm { name } => {
+                let tag = resolve::tag_exact(db, name).await?;
+                delete_tag_by_id(db, tag.id).await?;
+                println!("Successfully deleted tag with name: {}", tag.tag_name);
             }
         },
 
-        // // Parse "Del" Command
-        Some(Commands::Delete { subcommands }) => match subcommands.as_ref().unwrap() {
-            Subcommands::Notebook { name, desc: _ } => {
-                if name.is_none() {
-                    delete_all_notebooks(db).await?;
-                    println!("Successfully deleted all notebooks");
-                } else {
-                    delete_notebook_by_name(db, name).await?;
+        // Open the interactive browser
+        Some(Commands::Tui) => {
+            tui::run(db).await?;
+        }
+
+        // Serve the http api
+        Some(Commands::Serve { bind }) => {
+            server::serve(db.clone(), *bind).await?;
+        }
+
+        // Generate the static site
+        Some(Commands::Publish { out }) => {
+            let summary = publish::publish(db, out).await?;
+            println!(
+                "Successfully published {} notebooks, {} notes, {} tags and {} attachments to {}",
+                summary.notebooks,
+                summary.notes,
+                summary.tags,
+                summary.attachments,
+                out.display()
+            );
+        }
+
+        // Filter notes
+        Some(Commands::Find { query, save, saved, forget, list }) => {
+            if *saved {
+                for saved in get_all_saved_queries(db).await? {
+                    println!("@{}: {}", saved.query_name, saved.query)
+                }
+            } else if let Some(name) = forget {
+                println!("{}", delete_saved_query(db, name).await?);
+            } else {
+                let query = query.as_deref().unwrap_or_default();
+                let text = match query.strip_prefix('@') {
+                    Some(name) => get_saved_query(db, name).await?.query,
+                    None => query.to_string(),
+                };
+                let filter = query::parse(&text)
+                    .map_err(|err| anyhow!("{}\n{}", err, err.pointer(&text)))?;
+                if let Some(name) = save {
+                    save_query(db, name.to_owned(), text.clone()).await?;
+                    println!("Successfully saved query with name: {}", name);
+                }
+                let page = filter_notes(db, &filter, &list.options()).await?;
+                print_page(&page, list, output)?;
+            }
+        }
+
+        // Write in today's journal note
+        Some(Commands::Today { no_edit }) => {
+            let txn = db.begin().await?;
+            let note = journal::today(&txn, Local::now().date_naive()).await?;
+            txn.commit().await?;
+            if *no_edit {
+                println!("{}", note.content);
+            } else {
+                let content = editor::edit(&note.content, config.editor.as_deref())?;
+                if content != note.content {
+                    update_note(db, note.id, note.note_name, content).await?;
+                }
+                println!("Successfully updated journal");
+            }
+        }
+
+        // Summarize the journal
+        Some(Commands::Journal { week, date }) => {
+            let date = date.unwrap_or_else(|| Local::now().date_naive());
+            let days = match week {
+                true => journal::week(db, date).await?,
+                false => vec![journal::activity(db, date).await?],
+            };
+            match output {
+                Output::Json => println!("{}", serde_json::to_string_pretty(&days)?),
+                Output::Text => {
+                    for day in &days {
+                        println!(
+                            "{} {}: {} new, {} updated{}",
+                            day.date.format("%a"),
+                            day.date,
+                            day.created.len(),
+                            day.updated.len(),
+                            if day.entry.is_some() { ", journal written" } else { "" }
+                        );
+                        for title in &day.created {
+                            println!("  + {}", title);
+                        }
+                        for title in &day.updated {
+                            println!("  ~ {}", title);
+                        }
+                    }
+                    if *week {
+                        let created: usize = days.iter().map(|day| day.created.len()).sum();
+                        let updated: usize = days.iter().map(|day| day.updated.len()).sum();
+                        println!("\nweek: {} new, {} updated", created, updated);
+                    }
+                }
+            }
+        }
+
+        // Report study progress
+        Some(Commands::Progress { notebook, weeks }) => {
+            let notebook = match notebook {
+                Some(name) => Some(resolve::notebook(db, name).await?),
+                None => None,
+            };
+            let today = Local::now().date_naive();
+            let report =
+                progress::report(db, notebook.as_ref().map(|b| b.id), today, *weeks).await?;
+            match output {
+                Output::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+                Output::Text => {
+                    let name = notebook.map_or(String::from("all notes"), |b| b.notebook_name);
+                    let width = report
+                        .notebooks
+                        .iter()
+                        .map(|p| p.notebook.chars().count())
+                        .chain([name.chars().count()])
+                        .max()
+                        .unwrap_or_default();
+                    print_progress(&name, &report.overall, width);
+                    for progress in &report.notebooks {
+                        print_progress(&progress.notebook, &progress.counts, width);
+                    }
+                    if !report.notes.is_empty() {
+                        println!();
+                    }
+                    for note in &report.notes {
+                        println!("  {:<10} {}", note.status, note.title);
+                    }
+                    println!("\nstreak: {} day(s)\n", report.streak);
+                    print!("{}", progress::render(&report.calendar));
+                }
+            }
+        }
+
+        // Report database statistics
+        Some(Commands::Stats { days }) => {
+            let since = Utc::now().naive_utc() - chrono::Days::new(*days);
+            let stats = stats::collect(db, since).await?;
+            match output {
+                Output::Json => println!("{}", serde_json::to_string_pretty(&stats)?),
+                Output::Text => {
+                    println!("notebooks       {}", stats.notebooks);
+                    println!("notes           {}", stats.notes);
+                    println!("tags            {}", stats.tags);
+                    println!("average length  {:.0} characters", stats.average_length);
+                    print_counts("notes per notebook", &stats.notes_per_notebook, "");
+                    print_counts("notes per tag", &stats.notes_per_tag, "");
+                    print_counts("largest notes", &stats.largest_notes, " characters");
+                    print_names("notes in no notebook", &stats.orphan_notes);
+                    print_names("unused tags", &stats.unused_tags);
+                    let recent = &stats.recent;
+                    println!("\nrecent activity (last {} days)", days);
                     println!(
-                        "Successfully deleted notebook with name: {}",
-                        name.as_ref().unwrap_or(&"".to_string())
+                        "  {} created, {} updated, {} status changes",
+                        recent.created, recent.updated, recent.status_changes
                     );
+                    if !recent.last_updated.is_empty() {
+                        println!("  last updated: {}", recent.last_updated.join(", "));
+                    }
                 }
             }
-            Subcommands::Note { title: _, content: _ } => {
-                println!("Note: ");
-                todo!("Implement delete note functionality")
+        }
+
+        // Compile-check note snippets
+        Some(Commands::CheckSnippets { notebook, timeout }) => {
+            let notes = match notebook {
+                Some(name) => {
+                    let book = resolve::notebook(db, name).await?;
+                    get_notes_in_notebook(db, book.id).await?
+                }
+                None => get_all_notes(db).await?,
+            };
+            let timeout = Duration::from_secs(*timeout);
+            let (mut passed, mut failed, mut ignored) = (0, 0, 0);
+            for note in notes {
+                for (snippet, outcome) in snippets::check_content(&note.content, timeout).await? {
+                    match outcome {
+                        snippets::Outcome::Passed => {
+                            passed += 1;
+                            println!("ok      {} [block {}]", note.note_name, snippet.index);
+                        }
+                        snippets::Outcome::Ignored => {
+                            ignored += 1;
+                            println!("ignored {} [block {}]", note.note_name, snippet.index);
+                        }
+                        snippets::Outcome::Failed(reason) => {
+                            failed += 1;
+                            println!(
+                                "FAILED  {} [block {}]\n{}",
+                                note.note_name, snippet.index, reason
+                            );
+                        }
+                    }
+                }
             }
-            Subcommands::Tag { name: _ } => {
-                println!("Tag: ");
-                todo!("Implement delete tag functionality")
+            println!("\n{} passed; {} failed; {} ignored", passed, failed, ignored);
+            if failed > 0 {
+                anyhow::bail!("{} snippet(s) failed", failed);
             }
-        },
+        }
+
+        // Run a note snippet
+        Some(Commands::Run { title, block, timeout, save }) => {
+            // saving writes into the note, so it has to be the one meant
+            let note = match save {
+                true => resolve::note_exact(db, title).await?,
+                false => resolve::note(db, title).await?,
+            };
+            let Some(snippet) =
+                snippets::extract(&note.content).into_iter().nth(*block as usize - 1)
+            else {
+                anyhow::bail!("note '{}' has no rust block {}", note.note_name, block);
+            };
+            let dir = tempfile::tempdir()?;
+            let binary = match snippets::compile(&snippet, dir.path()).await? {
+                Ok(binary) => binary,
+                Err(stderr) => anyhow::bail!("block {} failed to compile\n{}", block, stderr),
+            };
+            let output = snippets::run(&binary, Duration::from_secs(*timeout)).await?;
+            print!("{}", output.stdout);
+            eprint!("{}", output.stderr);
+            if output.timed_out {
+                anyhow::bail!("block {} timed out after {}s", block, timeout);
+            }
+            // a failed run is not saved as if it were the block's output
+            match output.status {
+                Some(0) => {}
+                Some(code) => anyhow::bail!("block {} exited with status {}", block, code),
+                None => anyhow::bail!("block {} was killed by a signal", block),
+            }
+            if *save {
+                let content = snippets::save_output(&note.content, &snippet, &output);
+                update_note(db, note.id, note.note_name, content).await?;
+                println!("Successfully saved output to note");
+            }
+        }
+
+        // Run database checks
+        Some(Commands::Doctor { check: Some(DoctorCheck::Duplicates { merge }), .. }) => {
+            let groups = doctor::find_duplicates(db).await?;
+            for group in &groups {
+                let duplicates: Vec<String> = group
+                    .duplicates
+                    .iter()
+                    .map(|(id, name)| format!("'{}' ({})", name, id))
+                    .collect();
+                println!(
+                    "{} '{}' ({}) duplicated by {}",
+                    group.kind,
+                    group.keep.1,
+                    group.keep.0,
+                    duplicates.join(", ")
+                );
+            }
+            if groups.is_empty() {
+                println!("No duplicates found");
+            } else if *merge {
+                let txn = db.begin().await?;
+                let merged = doctor::merge_duplicates(&txn, &groups).await?;
+                txn.commit().await?;
+                println!("Successfully merged {} duplicates", merged);
+            } else {
+                println!("Run with --merge to merge them");
+            }
+        }
+
+        Some(Commands::Doctor { repair, check: None }) => {
+            let report = doctor::check(db).await?;
+            if output == Output::Json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_check(report.foreign_keys, "foreign keys");
+                print_check(report.integrity.is_empty(), "integrity check");
+                for problem in &report.integrity {
+                    println!("          {}", problem);
+                }
+                print_check(report.orphans.is_empty(), "orphan rows");
+                for orphan in &report.orphans {
+                    println!(
+                        "          {} row {} points to a missing {} record",
+                        orphan.table, orphan.rowid, orphan.parent
+                    );
+                }
+                print_check(report.empty_names.is_empty(), "empty names");
+                for empty in &report.empty_names {
+                    println!("          {} {} has an empty name", empty.kind, empty.id);
+                }
+                print_check(report.duplicates.is_empty(), "duplicates");
+                for group in &report.duplicates {
+                    let duplicates: Vec<String> =
+                        group.duplicates.iter().map(|(id, _)| id.to_string()).collect();
+                    println!(
+                        "          {} '{}' ({}) duplicated by {}",
+                        group.kind,
+                        group.keep.1,
+                        group.keep.0,
+                        duplicates.join(", ")
+                    );
+                }
+            }
+            let problems = report.repairable();
+            if problems > 0 && *repair {
+                let txn = db.begin().await?;
+                let repaired = doctor::repair(&txn, &report).await?;
+                txn.commit().await?;
+                println!("Successfully repaired {} problems", repaired);
+            } else if problems > 0 {
+                println!("Run with --repair to fix {} problems", problems);
+            }
+            if !report.foreign_keys {
+                anyhow::bail!("sqlite does not enforce foreign keys on this connection");
+            }
+            if !report.integrity.is_empty() {
+                anyhow::bail!("the database file is damaged, restore it from a backup");
+            }
+        }
+
+        // Completion scripts, docs, settings and templates were handled before connecting
+        Some(Commands::Completions { .. })
+        | Some(Commands::GenerateMan { .. })
+        | Some(Commands::Config { .. })
+        | Some(Commands::Template { .. }) => {}
 
         // Handle no command given
         None => {
@@ -180,3 +1276,118 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+// the text of --content, read from stdin when it is `-`, or the contents of
+// --content-file
+fn read_content(
+    content: &Option<String>,
+    content_file: &Option<PathBuf>,
+    limit: u64,
+) -> Result<Option<String>> {
+    match (content.as_deref(), content_file) {
+        (Some("-"), _) => input::read_content(Path::new("-"), limit).map(Some),
+        (Some(text), _) => Ok(Some(text.to_string())),
+        (None, Some(path)) => input::read_content(path, limit).map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+// how a record is shown in listings: its name, then what else sets it apart
+trait Line {
+    fn line(&self) -> String;
+}
+
+impl Line for notes::Model {
+    fn line(&self) -> String {
+        self.note_name.clone()
+    }
+}
+
+impl Line for notebooks::Model {
+    fn line(&self) -> String {
+        let mut line = self.notebook_name.clone();
+        if !self.description.is_empty() {
+            line.push_str(&format!(" - {}", self.description));
+        }
+        if let Some(filter) = &self.filter {
+            line.push_str(&format!("  (smart: {})", filter));
+        }
+        line
+    }
+}
+
+impl Line for tags::Model {
+    fn line(&self) -> String {
+        format!("#{}", self.tag_name)
+    }
+}
+
+// the titles of the notes under a notebook or tag
+fn print_titles(notes: &[notes::Model]) {
+    if notes.is_empty() {
+        println!("  no notes");
+    }
+    for note in notes {
+        println!("  {}", note.note_name);
+    }
+}
+
+// a titled two-column table of `stats`
+fn print_counts(title: &str, counts: &[stats::Count], unit: &str) {
+    println!("\n{}", title);
+    if counts.is_empty() {
+        println!("  none");
+    }
+    let width = counts.iter().map(|c| c.name.chars().count()).max().unwrap_or_default();
+    for count in counts {
+        println!("  {:<width$}  {:>5}{}", count.name, count.count, unit);
+    }
+}
+
+// a titled list of names, the first ten of a long one
+fn print_names(title: &str, names: &[String]) {
+    println!("\n{} ({})", title, names.len());
+    for name in names.iter().take(10) {
+        println!("  {}", name);
+    }
+    if names.len() > 10 {
+        println!("  ... and {} more", names.len() - 10);
+    }
+}
+
+// one line of `doctor`
+fn print_check(passed: bool, check: &str) {
+    println!("{:<8}{}", if passed { "ok" } else { "FAILED" }, check);
+}
+
+// one line of `progress`: the notes per status and the share understood
+fn print_progress(name: &str, counts: &progress::Counts, width: usize) {
+    println!(
+        "{:<width$}  {:>3} notes  {:>3} new  {:>3} learning  {:>3} understood  {:>3} mastered  {:>3}% done",
+        name,
+        counts.total,
+        counts.new,
+        counts.learning,
+        counts.understood,
+        counts.mastered,
+        counts.completion(),
+    );
+}
+
+// print a page of records with a "page X of Y" footer and how to get the next
+// one, or as json
+fn print_page<T: Line + Serialize>(page: &Page<T>, list: &ListArgs, output: Output) -> Result<()> {
+    if output == Output::Json {
+        println!("{}", serde_json::to_string_pretty(page)?);
+        return Ok(());
+    }
+    for item in &page.items {
+        println!("{}", item.line())
+    }
+    println!("-- {} --", page);
+    let next = list.offset + list.limit;
+    if next < page.total {
+        println!("next page: --offset {}", next);
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use migration::{Migrator, MigratorTrait};
+use modnote::crud::note::{create_note_with_links, get_all_notes};
+use sea_orm::{ConnectOptions, Database, DbConn, TransactionTrait};
+
+async fn memory_db() -> DbConn {
+    // a single connection so every statement sees the same in-memory database
+    let mut opts = ConnectOptions::new("sqlite::memory:");
+    opts.max_connections(1).sqlx_logging(false);
+    let db = Database::connect(opts).await.unwrap();
+    Migrator::up(&db, None).await.unwrap();
+    db
+}
+
+#[tokio::test]
+async fn failed_tag_step_leaves_no_orphan_note() {
+    let db = memory_db().await;
+
+    let txn = db.begin().await.unwrap();
+    // an empty tag name is rejected when the tag is saved, after the note was inserted
+    let result = create_note_with_links(
+        &txn,
+        String::from("orphan"),
+        String::from("content"),
+        &[],
+        &[String::new()],
+    )
+    .await;
+    assert!(result.is_err());
+    txn.rollback().await.unwrap();
+
+    assert!(get_all_notes(&db).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn committed_note_keeps_its_tags() {
+    let db = memory_db().await;
+
+    let txn = db.begin().await.unwrap();
+    let note = create_note_with_links(
+        &txn,
+        String::from("kept"),
+        String::from("content"),
+        &[],
+        &[String::from("rust")],
+    )
+    .await
+    .unwrap();
+    txn.commit().await.unwrap();
+
+    let tags = modnote::crud::tag::get_tags_for_note(&db, note.id).await.unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].tag_name, "rust");
+}
+
+#[tokio::test]
+async fn tag_differing_in_case_is_reused() {
+    let db = memory_db().await;
+    modnote::crud::tag::create_tag(&db, String::from("iterators")).await.unwrap();
+
+    let note = create_note_with_links(
+        &db,
+        String::from("Foo"),
+        String::from("hi"),
+        &[],
+        &[String::from("Iterators")],
+    )
+    .await
+    .unwrap();
+
+    let tags = modnote::crud::tag::get_tags_for_note(&db, note.id).await.unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].tag_name, "iterators");
+    assert_eq!(modnote::crud::tag::get_all_tags(&db).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn repeated_tag_is_linked_once() {
+    let db = memory_db().await;
+
+    let note = create_note_with_links(
+        &db,
+        String::from("Foo"),
+        String::from("hi"),
+        &[],
+        &[String::from("b"), String::from("b"), String::from(" B ")],
+    )
+    .await
+    .unwrap();
+
+    let tags = modnote::crud::tag::get_tags_for_note(&db, note.id).await.unwrap();
+    assert_eq!(tags.len(), 1);
+    assert_eq!(tags[0].tag_name, "b");
+}
+
+#[tokio::test]
+async fn merging_a_smart_notebook_is_refused() {
+    use modnote::crud::merge::merge_notebooks;
+    use modnote::crud::notebook::{create_notebook, create_smart_notebook, get_notes_in_notebook};
+
+    let db = memory_db().await;
+    let plain = create_notebook(&db, String::from("plain"), String::new()).await.unwrap();
+    let smart =
+        create_smart_notebook(&db, String::from("smart"), String::new(), String::from("tag:rust"))
+            .await
+            .unwrap();
+    create_note_with_links(&db, String::from("filed"), String::new(), &[plain.id], &[])
+        .await
+        .unwrap();
+
+    assert!(merge_notebooks(&db, smart.id, plain.id).await.is_err());
+    assert!(merge_notebooks(&db, plain.id, smart.id).await.is_err());
+
+    // both notebooks are left as they were
+    assert_eq!(get_notes_in_notebook(&db, plain.id).await.unwrap().len(), 1);
+    let smart = modnote::crud::notebook::get_notebook_by_id(&db, smart.id).await.unwrap();
+    assert_eq!(smart.filter.as_deref(), Some("tag:rust"));
+}